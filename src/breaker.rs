@@ -0,0 +1,57 @@
+//! Circuit breakers that skip failing servers.
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Tracks the recent consecutive failures of each server and opens its circuit once they
+// reach the threshold within the window. An open server is skipped until the cooldown
+// passes, after which it is tried again and a single failure opens it for another cooldown.
+pub(crate) struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    servers: Mutex<Vec<State>>,
+}
+
+#[derive(Clone, Default)]
+struct State {
+    // The times of the consecutive failures since the last success, oldest first.
+    failures: Vec<Instant>,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(servers: usize, threshold: u32, cooldown: Duration) -> Self {
+        CircuitBreaker {
+            threshold: threshold.max(1),
+            cooldown,
+            servers: Mutex::new(vec![State::default(); servers]),
+        }
+    }
+
+    // Whether the server is skipped.
+    pub(crate) fn is_open(&self, server: usize) -> bool {
+        let servers = self.servers.lock().unwrap();
+        servers[server]
+            .open_until
+            .is_some_and(|until| until > Instant::now())
+    }
+
+    // Records the outcome of a request to the server. Failures older than the window are
+    // forgotten.
+    pub(crate) fn record(&self, server: usize, success: bool, window: Duration) {
+        let mut servers = self.servers.lock().unwrap();
+        let state = &mut servers[server];
+        if success {
+            *state = State::default();
+            return;
+        }
+        let now = Instant::now();
+        state
+            .failures
+            .retain(|&failure| now.duration_since(failure) < window);
+        state.failures.push(now);
+        // A server that was already open needs a single failure to open again.
+        if state.failures.len() >= self.threshold as usize || state.open_until.is_some() {
+            state.open_until = Some(now + self.cooldown);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Cache of server responses.
+use crate::status::RCode;
+use crate::DnsResponse;
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Expired entries are removed when the cache grows past this size.
+const PURGE_THRESHOLD: usize = 1024;
+
+// Caches successful responses keyed by name and record type until their TTL expires.
+pub(crate) struct Cache {
+    entries: Mutex<HashMap<(String, u32), Entry>>,
+}
+
+struct Entry {
+    response: DnsResponse,
+    inserted: Instant,
+    expires: Instant,
+}
+
+impl Cache {
+    pub(crate) fn new() -> Self {
+        Cache {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Returns the cached response with the TTLs of its answers reduced by the time spent in
+    // the cache.
+    pub(crate) fn get(&self, key: &(String, u32)) -> Option<DnsResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        match entries.get(key) {
+            Some(entry) if entry.expires > now => {
+                let elapsed = now.duration_since(entry.inserted).as_secs() as u32;
+                let mut response = entry.response.clone();
+                if let Some(ref mut answers) = response.Answer {
+                    for answer in answers.iter_mut() {
+                        answer.TTL = answer.TTL.saturating_sub(elapsed);
+                    }
+                }
+                Some(response)
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    // Caches the response for the lowest TTL of its answers. It is reduced by up to a tenth
+    // at random so that entries cached together do not expire together, then clamped to the
+    // given bounds. Responses without answers are cached for the lower bound.
+    pub(crate) fn insert(
+        &self,
+        key: (String, u32),
+        response: &DnsResponse,
+        bounds: (Duration, Duration),
+        rng: &mut impl Rng,
+    ) {
+        match num::FromPrimitive::from_u32(response.Status) {
+            Some(RCode::NoError) | Some(RCode::NXDomain) => {}
+            _ => return,
+        }
+        let ttl = response
+            .Answer
+            .as_ref()
+            .and_then(|answers| answers.iter().map(|a| a.TTL).min())
+            .map(|ttl| Duration::from_secs(u64::from(ttl)).mul_f64(rng.gen_range(0.9..=1.0)))
+            .unwrap_or_default();
+        self.insert_for(key, response, ttl.max(bounds.0).min(bounds.1));
+    }
+
+    // Caches the response for the given time regardless of its status and TTLs.
+    pub(crate) fn insert_for(&self, key: (String, u32), response: &DnsResponse, ttl: Duration) {
+        if ttl == Duration::from_secs(0) {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= PURGE_THRESHOLD {
+            entries.retain(|_, entry| entry.expires > now);
+        }
+        entries.insert(
+            key,
+            Entry {
+                response: response.clone(),
+                inserted: now,
+                expires: now + ttl,
+            },
+        );
+    }
+}
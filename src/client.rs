@@ -1,56 +1,48 @@
 //! HTTPS client to query DoH servers.
 use async_trait::async_trait;
+use hyper::{header::HeaderMap, Body, Response, Result as HyperResult, Uri};
 
-use hyper::{
-    client::{connect::dns::GaiResolver, HttpConnector},
-    Body, Client, Request, Response, Result as HyperResult, Uri,
-};
-use hyper_tls::HttpsConnector;
+#[cfg(feature = "h3")]
+pub use crate::h3_client::H3DnsClient;
+#[cfg(feature = "hyper-client")]
+pub use crate::hyper_client::{HyperDnsClient, StaticResolver, TlsInfo, TlsVersion};
 
 /// Creates a `GET` request over the given `URI` and returns its response. It is used to
 /// request data from DoH servers.
 #[async_trait]
-pub trait DnsClient: Default {
+pub trait DnsClient: Default + Send + Sync {
     async fn get(&self, uri: Uri) -> HyperResult<Response<Body>>;
-}
 
-/// Hyper-based DNS client over SSL and with a static resolver to resolve DNS server names
-/// such as `dns.google` since Google does not accept request over `8.8.8.8` like Cloudflare
-/// does over `1.1.1.1`.
-pub struct HyperDnsClient {
-    client: Client<HttpsConnector<HttpConnector<GaiResolver>>>,
-}
+    /// Creates a `POST` request over the given `URI` with the given form encoded body and
+    /// returns its response. It is used when queries are sent in the request body with
+    /// [Dns::with_json_post](crate::Dns::with_json_post). By default, it responds with a
+    /// `501 Not Implemented` status.
+    async fn post(&self, _uri: Uri, _body: String) -> HyperResult<Response<Body>> {
+        let mut res = Response::new(Body::empty());
+        *res.status_mut() = hyper::StatusCode::NOT_IMPLEMENTED;
+        Ok(res)
+    }
 
-impl Default for HyperDnsClient {
-    fn default() -> HyperDnsClient {
-        let mut http_connector = HttpConnector::new();
-        http_connector.enforce_http(false);
-        let mut connector = HttpsConnector::from((
-            http_connector,
-            native_tls::TlsConnector::builder()
-                .danger_accept_invalid_hostnames(true)
-                .build()
-                .unwrap()
-                .into(),
-        ));
-        connector.https_only(true);
-        HyperDnsClient {
-            client: Client::builder().build(connector),
-        }
+    /// Creates a `GET` request like [DnsClient::get] with the given additional headers, set
+    /// with [Dns::with_headers](crate::Dns::with_headers). By default, the headers are
+    /// ignored.
+    async fn get_with_headers(
+        &self,
+        uri: Uri,
+        _headers: &HeaderMap,
+    ) -> HyperResult<Response<Body>> {
+        self.get(uri).await
     }
-}
 
-#[async_trait]
-impl DnsClient for HyperDnsClient {
-    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
-        // The reason to build a request manually is to set the Accept header required by
-        // DNS servers.
-        let req = Request::builder()
-            .method("GET")
-            .uri(uri)
-            .header("Accept", "application/dns-json")
-            .body(Body::default())
-            .expect("request builder");
-        self.client.request(req).await
+    /// Creates a `POST` request like [DnsClient::post] with the given additional headers,
+    /// set with [Dns::with_headers](crate::Dns::with_headers). By default, the headers are
+    /// ignored.
+    async fn post_with_headers(
+        &self,
+        uri: Uri,
+        body: String,
+        _headers: &HeaderMap,
+    ) -> HyperResult<Response<Body>> {
+        self.post(uri, body).await
     }
 }
@@ -1,32 +1,475 @@
+use crate::breaker::CircuitBreaker;
+use crate::cache::Cache;
 use crate::client::DnsClient;
 use crate::error::{DnsError, QueryError};
+use crate::lenient;
+use crate::records::{
+    self, CertRecord, DmarcRecord, DomainRecords, DsRecord, IpseckeyRecord, MxRecord, NsecRecord,
+    RrsigRecord, SoaRecord,
+};
+use crate::rtype::RecordType;
+use crate::server::{ServerConfig, DEFAULT_SERVERS};
+use crate::single_flight::SingleFlight;
+use crate::stats::{Counters, DnsStats};
 use crate::status::RCode;
-use crate::{Dns, DnsAnswer, DnsHttpsServer, DnsResponse};
-use hyper::Uri;
+use crate::strategy::{Latencies, Strategy};
+use crate::timer::{self, Timer};
+use crate::{
+    AnswerDiff, AttributedAnswer, Dns, DnsAnswer, DnsFlags, DnsHttpsServer, DnsReply, DnsResponse,
+    HostResult, IdnaConfig, IssuedQuery,
+};
+use futures_util::future::join_all;
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use futures_util::{join, try_join};
+use hyper::{
+    header::{HeaderMap, CONTENT_ENCODING, LOCATION, RETRY_AFTER},
+    Body, Response, Uri,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use log::error;
-use tokio::time::timeout;
+
+/// The HTTP statuses on which a request is retried on the next server by default: 429, 500,
+/// 502, 503 and 504.
+pub const DEFAULT_RETRYABLE_STATUSES: [u16; 5] = [429, 500, 502, 503, 504];
+
+// The period within which failures count toward opening a circuit breaker by default.
+const DEFAULT_BREAKER_WINDOW: Duration = Duration::from_secs(60);
+
+// The longest time a response is cached by default.
+const DEFAULT_CACHE_TTL_MAX: Duration = Duration::from_secs(24 * 60 * 60);
+
+// The timeout of servers without one unless another is set with `Dns::with_default_timeout`.
+const DEFAULT_SERVER_TIMEOUT: Duration = Duration::from_secs(5);
+
+// The longest query URL that is sent. Longer URLs are commonly rejected by servers and
+// proxies with a 414 status.
+const MAX_URL_LENGTH: usize = 2048;
+
+// The longest name in its textual form without a trailing dot, as defined in RFC 1035.
+const MAX_NAME_LENGTH: usize = 253;
+
+// The number of queries of `Dns::resolve_batch` that are resolved at the same time.
+const BATCH_CONCURRENCY: usize = 16;
+
+// The delay before the first retry on the same server.
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+impl<C: DnsClient> Default for Dns<C, ServerConfig> {
+    /// Creates an instance with the servers of [DEFAULT_SERVERS]: Google first and
+    /// Cloudflare second.
+    fn default() -> Self {
+        Dns::with_servers(&DEFAULT_SERVERS).unwrap()
+    }
+}
 
 impl<C: DnsClient, S: DnsHttpsServer> Dns<C, S> {
     /// Creates an instance with the given servers along with their respective timeouts
     /// (in seconds). These servers are tried in the given order. If a request fails on
     /// the first one, each subsequent server is tried. Only on certain failures a new
-    /// request is retried such as a connection failure or certain server return codes.
+    /// request is retried such as a connection failure or certain server return codes. A
+    /// server without a URI, such as a custom [ServerConfig] loaded without its `uri`, is
+    /// rejected with a [QueryError::InvalidEndpoint] error naming its position in the list.
     pub fn with_servers(servers: &[S]) -> Result<Self, DnsError> {
+        Self::with_client(C::default(), servers)
+    }
+
+    /// Creates an instance like [Dns::with_servers] but with the given client instead of a
+    /// default one. This allows using a configured client such as
+    /// [HyperDnsClient::with_bootstrap_ips](crate::client::HyperDnsClient::with_bootstrap_ips).
+    pub fn with_client(client: C, servers: &[S]) -> Result<Self, DnsError> {
         if servers.is_empty() {
             return Err(DnsError::NoServers);
         }
+        if let Some(i) = servers.iter().position(|s| s.uri().trim().is_empty()) {
+            return Err(DnsError::Query(QueryError::InvalidEndpoint(format!(
+                "server {} of the list has no URI",
+                i
+            ))));
+        }
         Ok(Dns {
-            client: C::default(),
+            client,
             servers: servers.to_vec(),
+            lenient: false,
+            timer: timer::default_timer(),
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
+            per_server_retries: 0,
+            stop_on: None,
+            single_flight: None,
+            on_exhausted: None,
+            transform: None,
+            counters: Counters::default(),
+            edns_buffer_size: None,
+            cache: None,
+            cache_ttl_bounds: (Duration::from_secs(0), DEFAULT_CACHE_TTL_MAX),
+            min_cacheable_ttl: Duration::from_secs(0),
+            strategy: Strategy::default(),
+            latencies: Latencies::new(servers.len()),
+            breaker: None,
+            breaker_window: DEFAULT_BREAKER_WINDOW,
+            race_accepts_empty: true,
+            local_names: Vec::new(),
+            max_answers: None,
+            idna: IdnaConfig::default(),
+            keep_cnames: false,
+            json_post: false,
+            default_timeout: DEFAULT_SERVER_TIMEOUT,
+            empty_as_error: false,
+            log_after_failures: 1,
+            strict_name_match: false,
+            sticky_server: None,
+            headers: HeaderMap::new(),
+            max_redirects: 0,
+            rng: Mutex::new(StdRng::from_entropy()),
+            flags: DnsFlags::default(),
+            #[cfg(feature = "system-fallback")]
+            system_fallback: false,
+            #[cfg(feature = "tokio")]
+            concurrency: None,
         })
     }
 
+    /// Sets the EDNS0 UDP buffer size hint sent to the servers, which signals the maximum
+    /// response size the upstream resolver should use. It is only sent to servers that
+    /// support it as given by [DnsHttpsServer::edns_buffer_size_param].
+    pub fn with_edns_buffer_size(mut self, size: u16) -> Self {
+        self.edns_buffer_size = Some(size);
+        self
+    }
+
+    /// Sets the names that are answered locally instead of being sent to the servers, such as
+    /// `localhost` or the special-use names of RFC 6761. A name in the list matches itself
+    /// and its subdomains, while a name starting with a dot such as `.local` only matches
+    /// subdomains. Matching names under `localhost` resolve to the loopback addresses with a
+    /// TTL of 0, and any other matching name returns a [DnsError::LocalName] error. By
+    /// default, all names are sent to the servers.
+    pub fn with_local_names(mut self, names: &[&str]) -> Self {
+        self.local_names = names
+            .iter()
+            .map(|name| name.trim().trim_end_matches('.').to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// Sets the recursion desired (`RD`) flag of queries. When it is disabled, `rd=0` is sent
+    /// to the servers to ask for an answer from their cache or their own data only. This is
+    /// best-effort as most DoH servers, including Google and Cloudflare, ignore it and always
+    /// recurse. Whether recursion was desired is given by
+    /// [DnsReply::recursion_desired](crate::DnsReply::recursion_desired). It is enabled by
+    /// default.
+    pub fn with_recursion_desired(mut self, desired: bool) -> Self {
+        self.flags.recursion_desired = desired;
+        self
+    }
+
+    /// Sets all the header flags sent with queries at once, including the recursion desired
+    /// flag of [Dns::with_recursion_desired]. Whether a server honors a flag can be seen in
+    /// the flags of its response with [Dns::resolve_with_flags].
+    pub fn with_flags(mut self, flags: DnsFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Returns the header flags sent with queries.
+    pub fn flags(&self) -> DnsFlags {
+        self.flags
+    }
+
+    /// Returns a snapshot of the statistics of the queries made by this instance since it
+    /// was created.
+    pub fn stats(&self) -> DnsStats {
+        self.counters.snapshot()
+    }
+
+    /// Sets a callback invoked once when a query fails on all servers. It is given the name
+    /// that was queried and the error returned by each server that was tried along with its
+    /// URI, in the order the servers were tried. It is not called for failures that happen
+    /// before any request is made such as an invalid name.
+    pub fn on_exhausted<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&str, &[(String, QueryError)]) + Send + Sync + 'static,
+    {
+        self.on_exhausted = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets how internationalized names are converted to their ASCII form before being
+    /// queried. The name that is queried for a given configuration can be seen with
+    /// [Dns::build_query_url].
+    pub fn with_idna_config(mut self, config: IdnaConfig) -> Self {
+        self.idna = config;
+        self
+    }
+
+    /// Enables or disables keeping the CNAME answers in the results of the `resolve_` methods
+    /// of other record types, such as [Dns::resolve_a], ahead of the answers of the requested
+    /// type. A name that is an alias whose target records were not included by the server
+    /// then returns its CNAME record instead of no answers, which tells that the alias can be
+    /// followed. It is disabled by default.
+    pub fn with_keep_cnames(mut self, keep: bool) -> Self {
+        self.keep_cnames = keep;
+        self
+    }
+
+    /// Enables or disables dropping the answers whose name does not pertain to the queried
+    /// name from the results of the `resolve_` methods of each record type, such as
+    /// [Dns::resolve_a]. An answer pertains to the query if its name is the queried name or a
+    /// target of the CNAME chain starting from it. This guards against mismatched responses.
+    /// It is disabled by default.
+    pub fn with_strict_name_match(mut self, enabled: bool) -> Self {
+        self.strict_name_match = enabled;
+        self
+    }
+
+    /// Sets headers that are added to every request to the servers, such as `Accept-Language`
+    /// or a region hint for providers that tailor their answers. They replace any header of
+    /// the same name set by the client. They are sent by clients that implement
+    /// [DnsClient::get_with_headers], such as [HyperDnsClient](crate::client::HyperDnsClient).
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Follows up to the given number of redirects (`301`, `302`, `307` and `308` statuses)
+    /// from the servers, such as when a provider moves its endpoint. Only redirects to an
+    /// absolute `https` URL that was not visited yet by the request are followed; other
+    /// redirects fail like other unexpected statuses. Each redirect counts as a request
+    /// within the server timeout. By default, redirects are not followed.
+    pub fn with_redirects(mut self, max: usize) -> Self {
+        self.max_redirects = max;
+        self
+    }
+
+    /// Enables or disables sending the query parameters in a form encoded `POST` body instead
+    /// of the URL, while still receiving JSON responses. This keeps queried names out of URL
+    /// access logs and avoids URL length limits. The server must accept such requests and
+    /// the client must implement [DnsClient::post]. It is disabled by default.
+    pub fn with_json_post(mut self, enabled: bool) -> Self {
+        self.json_post = enabled;
+        self
+    }
+
+    /// Limits the number of answers returned by the `resolve_` methods of each record type,
+    /// such as [Dns::resolve_a], to the first `max` answers of the requested type. This
+    /// bounds the size of results for names with many records. `CNAME` records kept with
+    /// [Dns::with_keep_cnames] do not count toward the limit. By default, all answers are
+    /// returned.
+    pub fn with_max_answers(mut self, max: usize) -> Self {
+        self.max_answers = Some(max);
+        self
+    }
+
+    /// Enables or disables returning a [DnsError::NoAnswers] error instead of an empty list
+    /// from the `resolve_` methods of each record type, such as [Dns::resolve_a], when a
+    /// query succeeds without answers. This suits callers that always expect at least one
+    /// record. It is disabled by default.
+    pub fn with_empty_as_error(mut self, enabled: bool) -> Self {
+        self.empty_as_error = enabled;
+        self
+    }
+
+    /// Sets a transform applied to the answers of queries made with the `resolve_` methods of
+    /// each record type, such as [Dns::resolve_a], before they are returned. It is given the
+    /// queried name, the record type and the answers of that type, and returns the answers
+    /// to use instead. This allows overriding or filtering answers, e.g. to block names.
+    pub fn with_transform<F>(mut self, transform: F) -> Self
+    where
+        F: Fn(&str, u32, Vec<DnsAnswer>) -> Vec<DnsAnswer> + Send + Sync + 'static,
+    {
+        self.transform = Some(Box::new(transform));
+        self
+    }
+
+    /// Enables or disables the de-duplication of concurrent identical queries. When enabled,
+    /// a query for a name and record type that is already in flight waits for that query and
+    /// shares its result instead of making its own requests. It is disabled by default.
+    pub fn with_single_flight(mut self, enabled: bool) -> Self {
+        self.single_flight = if enabled {
+            Some(SingleFlight::new())
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Sets the response codes that end the query without trying the next server. A response
+    /// with any other code, or a `NoError` response without answers unless `NoError` is
+    /// given, is only returned if no other server gives an accepted response. By default, any
+    /// response from a server ends the query.
+    pub fn with_stop_on(mut self, rcodes: &[RCode]) -> Self {
+        self.stop_on = Some(rcodes.to_vec());
+        self
+    }
+
+    /// Sets how many times a request is retried on the same server before trying the next
+    /// one. Only retryable errors such as connection failures are retried. The delay before
+    /// each retry starts at 100 milliseconds and doubles every time. A longer delay asked by a
+    /// server with the `Retry-After` header of a 503 response is waited instead, up to the
+    /// server timeout. By default, requests are not retried on the same server.
+    pub fn with_per_server_retries(mut self, retries: u32) -> Self {
+        self.per_server_retries = retries;
+        self
+    }
+
+    /// Sets the HTTP statuses on which a request is retried on the next server. A response
+    /// with any other error status ends the query with that error. The default statuses are
+    /// [DEFAULT_RETRYABLE_STATUSES].
+    pub fn with_retryable_statuses(mut self, statuses: &[u16]) -> Self {
+        self.retryable_statuses = statuses.to_vec();
+        self
+    }
+
+    /// Sets the number of servers a query must fail on before their errors are logged. The
+    /// error of each server is logged from the `n`th failed server of a query on, so that
+    /// transient errors of a server followed by a successful answer from the next one are
+    /// not logged. By default, the error of every failed server is logged.
+    pub fn with_log_after_failures(mut self, n: usize) -> Self {
+        self.log_after_failures = n;
+        self
+    }
+
+    /// Sets the timeout of servers that do not have one of their own, which are those whose
+    /// [DnsHttpsServer::timeout] is zero such as a [ServerConfig](crate::server::ServerConfig)
+    /// without `timeout_secs`. It is 5 seconds by default.
+    pub fn with_default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = timeout;
+        self
+    }
+
+    /// Sets the timer used to enforce the timeout of each server. By default, a `tokio` timer
+    /// is used if the `tokio-timer` feature is enabled. Otherwise, no timeouts are enforced
+    /// until a timer is set.
+    pub fn with_timer<T: Timer + 'static>(mut self, timer: T) -> Self {
+        self.timer = Some(Box::new(timer));
+        self
+    }
+
+    /// Enables or disables lenient parsing of server responses. By default responses must
+    /// match the documented JSON schema exactly. In lenient mode, common provider quirks are
+    /// accepted such as numbers given as strings or missing fields, and parsing errors name
+    /// the offending field.
+    pub fn with_lenient_parsing(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Enables or disables the caching of responses. Responses are cached until the lowest
+    /// TTL of their answers expires, within the bounds set with
+    /// [Dns::with_cache_ttl_bounds]. It is disabled by default.
+    pub fn with_cache(mut self, enabled: bool) -> Self {
+        self.cache = if enabled { Some(Cache::new()) } else { None };
+        self
+    }
+
+    /// Sets the minimum and maximum time a response is cached regardless of its TTL. This
+    /// prevents re-querying records with very low TTLs and keeping records with very long
+    /// ones. By default, responses are cached for at most a day and responses without
+    /// answers are not cached.
+    pub fn with_cache_ttl_bounds(mut self, min: Duration, max: Duration) -> Self {
+        self.cache_ttl_bounds = (min, max.max(min));
+        self
+    }
+
+    /// Sets the lowest TTL of answers that are worth caching. Responses with an answer whose
+    /// TTL is below it are not cached at all, rather than being cached for the lower bound of
+    /// [Dns::with_cache_ttl_bounds]. Responses without answers are not affected. By default,
+    /// answers are cached regardless of their TTL.
+    pub fn with_min_cacheable_ttl(mut self, ttl: Duration) -> Self {
+        self.min_cacheable_ttl = ttl;
+        self
+    }
+
+    /// Enables or disables trying the last server that answered first for the next queries,
+    /// before the others in the order of the strategy. This keeps the answers of a session
+    /// consistent and reuses the connection to that server. It is disabled by default.
+    pub fn with_sticky_server(mut self, enabled: bool) -> Self {
+        // No server has answered yet.
+        self.sticky_server = enabled.then(|| AtomicUsize::new(usize::MAX));
+        self
+    }
+
+    /// Seeds the random number generator used by the randomized features, such as the server
+    /// chosen by [Strategy::Weighted] and the jitter of cache expiry, so that they behave
+    /// the same on every run. This is meant for tests. By default, the generator is seeded
+    /// from the operating system.
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.rng = Mutex::new(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    /// Sets the strategy that chooses the order in which servers are tried. By default,
+    /// servers are tried in the given order.
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Enables a circuit breaker on each server. After `threshold` consecutive failed
+    /// requests within the window set with [Dns::with_circuit_breaker_window], a server is
+    /// skipped for the `cooldown` period and queries go straight to the next server. It is
+    /// then tried again and skipped for another period if it fails. If all servers are
+    /// skipped, they are all tried anyway.
+    pub fn with_circuit_breaker(mut self, threshold: u32, cooldown: Duration) -> Self {
+        self.breaker = Some(CircuitBreaker::new(self.servers.len(), threshold, cooldown));
+        self
+    }
+
+    /// Sets the period within which the consecutive failures of a server count toward
+    /// opening its circuit breaker set with [Dns::with_circuit_breaker]. Older failures are
+    /// forgotten, so that occasional failures spread over a long time do not skip a server.
+    /// The default window is 60 seconds.
+    pub fn with_circuit_breaker_window(mut self, window: Duration) -> Self {
+        self.breaker_window = window;
+        self
+    }
+
+    /// Sets whether a response without answers, such as `NoData` or `NXDomain`, ends
+    /// [Dns::resolve_fastest]. When it does not, the race goes on for a server that gives
+    /// answers and the first response without answers is only returned if none does. By
+    /// default, the first response of any kind ends the race.
+    pub fn with_race_accepts_empty(mut self, accepts: bool) -> Self {
+        self.race_accepts_empty = accepts;
+        self
+    }
+
+    /// Enables or disables resolving `A` and `AAAA` records with the system resolver when all
+    /// servers fail. The system resolver usually sends plain DNS queries, which gives up the
+    /// privacy of DoH for availability. Its answers have a TTL of 0 since it does not expose
+    /// the TTLs of records. It requires the `system-fallback` feature and a `tokio` runtime,
+    /// and is disabled by default.
+    #[cfg(feature = "system-fallback")]
+    pub fn with_system_fallback(mut self, enabled: bool) -> Self {
+        self.system_fallback = enabled;
+        self
+    }
+
+    /// Limits the number of requests in flight to the servers across all queries of this
+    /// instance. Requests over the limit wait for an earlier one to complete before being
+    /// sent, and their server timeout only starts then. This prevents bursts of queries from
+    /// getting rate limited. It requires the `tokio` dependency, which is enabled by the
+    /// default `tokio-timer` feature, but works on any executor.
+    #[cfg(feature = "tokio")]
+    pub fn with_max_concurrent(mut self, max: usize) -> Self {
+        self.concurrency = Some(tokio::sync::Semaphore::new(max.max(1)));
+        self
+    }
+
     /// Returns MX records in order of priority for the given name. It removes the priorities
     /// from the data.
-    pub async fn resolve_mx_and_sort(&self, domain: &str) -> Result<Vec<DnsAnswer>, DnsError> {
-        match self.client_request(domain, &RTYPE_mx).await {
-            Err(e) => Err(DnsError::Query(e)),
+    pub async fn resolve_mx_and_sort(
+        &self,
+        domain: impl AsRef<str>,
+    ) -> Result<Vec<DnsAnswer>, DnsError> {
+        let domain = domain.as_ref();
+        match self.client_request(domain, &RTYPE_mx, None).await {
+            Err(e) => Err(e),
             Ok(res) => match num::FromPrimitive::from_u32(res.Status) {
                 Some(RCode::NoError) => {
                     let mut mxs = res
@@ -63,81 +506,1744 @@ impl<C: DnsClient, S: DnsHttpsServer> Dns<C, S> {
         }
     }
 
+    /// Returns the TXT records for the given name that start with the given prefix such as
+    /// `v=spf1` or `v=DMARC1`. The character strings of each record are unquoted and
+    /// concatenated before matching.
+    pub async fn resolve_txt_matching(
+        &self,
+        name: impl AsRef<str>,
+        prefix: &str,
+    ) -> Result<Vec<String>, DnsError> {
+        let name = name.as_ref();
+        Ok(self
+            .resolve_txt(name)
+            .await?
+            .iter()
+            .map(|a| records::txt_to_string(&a.data))
+            .filter(|txt| txt.starts_with(prefix))
+            .collect())
+    }
+
+    /// Returns the character strings of each TXT record for the given name without
+    /// concatenating them, as opposed to [Dns::resolve_txt_matching]. Each string is unquoted
+    /// and unescaped. This shows how long records such as DKIM keys were split when published.
+    pub async fn resolve_txt_chunks(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<Vec<String>>, DnsError> {
+        Ok(self
+            .resolve_txt(name)
+            .await?
+            .iter()
+            .map(|a| records::txt_chunks(&a.data))
+            .collect())
+    }
+
+    /// Returns the DMARC policy of the given domain, queried at `_dmarc.<domain>`. It is
+    /// `None` if the domain has no DMARC record, has more than one, or if the record cannot
+    /// be parsed, which are all treated as having no policy by RFC 7489.
+    pub async fn resolve_dmarc(
+        &self,
+        domain: impl AsRef<str>,
+    ) -> Result<Option<DmarcRecord>, DnsError> {
+        let name = format!("_dmarc.{}", domain.as_ref().trim_end_matches('.'));
+        let records = match self.resolve_txt_matching(name, "v=DMARC1").await {
+            Ok(records) => records,
+            Err(DnsError::Status(RCode::NXDomain)) | Err(DnsError::NoAnswers) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        match records.as_slice() {
+            [record] => Ok(DmarcRecord::parse(record)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the sorted hostnames of the authoritative name servers for the given name.
+    /// The trailing dot of each hostname is removed.
+    pub async fn resolve_nameservers(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<String>, DnsError> {
+        let name = name.as_ref();
+        let mut nameservers = self
+            .resolve_ns(name)
+            .await?
+            .iter()
+            .map(|a| a.data.trim_end_matches('.').to_string())
+            .collect::<Vec<_>>();
+        nameservers.sort_unstable();
+        nameservers.dedup();
+        Ok(nameservers)
+    }
+
+    /// Returns the `A`, `AAAA`, `MX`, `TXT` and `NS` records for the given name. These are
+    /// queried concurrently and the first error of any of them is returned.
+    pub async fn resolve_common(&self, name: impl AsRef<str>) -> Result<DomainRecords, DnsError> {
+        let name = name.as_ref();
+        let (a, aaaa, mx, txt, ns) = try_join!(
+            self.resolve_a(name),
+            self.resolve_aaaa(name),
+            self.resolve_mx(name),
+            self.resolve_txt(name),
+            self.resolve_nameservers(name),
+        )?;
+        let mut mx = mx
+            .iter()
+            .filter_map(|a| MxRecord::parse(&a.data))
+            .collect::<Vec<_>>();
+        mx.sort_by_key(|m| m.preference);
+        Ok(DomainRecords {
+            a: a.iter().filter_map(|a| a.data.parse().ok()).collect(),
+            aaaa: aaaa.iter().filter_map(|a| a.data.parse().ok()).collect(),
+            mx,
+            txt: txt
+                .iter()
+                .map(|a| records::txt_to_string(&a.data))
+                .collect(),
+            ns,
+        })
+    }
+
+    /// Returns the IPv4 and IPv6 addresses of the given name with a result for each address
+    /// family. The `A` and `AAAA` records are queried concurrently, and answers that are not
+    /// valid addresses are skipped.
+    pub async fn resolve_host_detailed(&self, name: impl AsRef<str>) -> HostResult {
+        let name = name.as_ref();
+        let (a, aaaa) = join!(self.resolve_a(name), self.resolve_aaaa(name));
+        HostResult {
+            v4: a.map(|a| {
+                a.iter()
+                    .filter_map(|a| a.data.trim().parse().ok())
+                    .collect()
+            }),
+            v6: aaaa.map(|a| {
+                a.iter()
+                    .filter_map(|a| a.data.trim().parse().ok())
+                    .collect()
+            }),
+        }
+    }
+
+    /// Returns the IPv4 addresses of the `A` records for the given name. Answers that are not
+    /// valid addresses are skipped.
+    pub async fn resolve_ipv4(&self, name: impl AsRef<str>) -> Result<Vec<Ipv4Addr>, DnsError> {
+        Ok(self
+            .resolve_a(name)
+            .await?
+            .iter()
+            .filter_map(|a| a.data.trim().parse().ok())
+            .collect())
+    }
+
+    /// Returns the IPv6 addresses of the `AAAA` records for the given name. Answers that are
+    /// not valid addresses are skipped.
+    pub async fn resolve_ipv6(&self, name: impl AsRef<str>) -> Result<Vec<Ipv6Addr>, DnsError> {
+        Ok(self
+            .resolve_aaaa(name)
+            .await?
+            .iter()
+            .filter_map(|a| a.data.trim().parse().ok())
+            .collect())
+    }
+
+    /// Returns whether the given name has at least one record of the given type. The record
+    /// type is given by name such as `a` or `MX`. A name without such records or that does
+    /// not exist (`NXDomain`) returns `false`, while the failure of the query or any other
+    /// error of the server is returned as an error. Only answers of the requested type are
+    /// counted, regardless of [Dns::with_keep_cnames], [Dns::with_max_answers] or
+    /// [Dns::with_transform].
+    pub async fn has_record(&self, name: impl AsRef<str>, rtype: &str) -> Result<bool, DnsError> {
+        let rtype = match rtype_from_name(rtype) {
+            Some(rtype) => rtype,
+            None => return Err(DnsError::InvalidRecordType),
+        };
+        let name = name.as_ref();
+        let mut res = self.client_request(name, rtype, None).await?;
+        self.drop_unrelated(name, &mut res);
+        match process_response(res, rtype) {
+            Ok(answers) => Ok(!answers.is_empty()),
+            Err(DnsError::Status(RCode::NXDomain)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the first IP address of the given name, or `None` if it has none. The `A`
+    /// records are queried first and the `AAAA` records only if there is no IPv4 address.
+    /// Answers that are not valid addresses are skipped.
+    pub async fn resolve_first_ip(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Option<IpAddr>, DnsError> {
+        let name = name.as_ref();
+        let first = |answers: Vec<DnsAnswer>| {
+            answers
+                .iter()
+                .find_map(|a| a.data.trim().parse::<IpAddr>().ok())
+        };
+        let v4 = match self.resolve_a(name).await {
+            Ok(answers) => first(answers),
+            // A name with only IPv6 addresses has no `A` records.
+            Err(DnsError::NoAnswers) => None,
+            Err(e) => return Err(e),
+        };
+        if v4.is_some() {
+            return Ok(v4);
+        }
+        match self.resolve_aaaa(name).await {
+            Ok(answers) => Ok(first(answers)),
+            Err(DnsError::NoAnswers) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the IPSECKEY records for the given name parsed into their fields. Records
+    /// that cannot be parsed are skipped.
+    pub async fn resolve_ipseckey_structured(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<IpseckeyRecord>, DnsError> {
+        let name = name.as_ref();
+        Ok(self
+            .resolve_ipseckey(name)
+            .await?
+            .iter()
+            .filter_map(|a| IpseckeyRecord::parse(&a.data))
+            .collect())
+    }
+
+    /// Returns the CERT records for the given name parsed into their fields. Records that
+    /// cannot be parsed are skipped.
+    pub async fn resolve_cert_structured(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<CertRecord>, DnsError> {
+        let name = name.as_ref();
+        Ok(self
+            .resolve_cert(name)
+            .await?
+            .iter()
+            .filter_map(|a| CertRecord::parse(&a.data))
+            .collect())
+    }
+
+    /// Returns the DS records for the given name parsed into their fields. Records that
+    /// cannot be parsed are skipped.
+    pub async fn resolve_ds_structured(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<DsRecord>, DnsError> {
+        let name = name.as_ref();
+        Ok(self
+            .resolve_ds(name)
+            .await?
+            .iter()
+            .filter_map(|a| DsRecord::parse(&a.data))
+            .collect())
+    }
+
+    /// Returns the CDS records for the given name parsed into their fields. Records that
+    /// cannot be parsed are skipped.
+    pub async fn resolve_cds_structured(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<DsRecord>, DnsError> {
+        let name = name.as_ref();
+        Ok(self
+            .resolve_cds(name)
+            .await?
+            .iter()
+            .filter_map(|a| DsRecord::parse(&a.data))
+            .collect())
+    }
+
+    /// Returns the NSEC records for the given name parsed into their next domain and the
+    /// record types of their type bitmap. Records that cannot be parsed, such as those with
+    /// an unknown type mnemonic, are skipped.
+    pub async fn resolve_nsec_structured(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<NsecRecord>, DnsError> {
+        let name = name.as_ref();
+        Ok(self
+            .resolve_nsec(name)
+            .await?
+            .iter()
+            .filter_map(|a| NsecRecord::parse(&a.data))
+            .collect())
+    }
+
+    /// Returns the RRSIG records for the given name parsed into their fields, with the type
+    /// covered as a number and the expiration and inception as times. Records that cannot
+    /// be parsed are skipped.
+    pub async fn resolve_rrsig_structured(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<RrsigRecord>, DnsError> {
+        let name = name.as_ref();
+        Ok(self
+            .resolve_rrsig(name)
+            .await?
+            .iter()
+            .filter_map(|a| RrsigRecord::parse(&a.data))
+            .collect())
+    }
+
+    /// Returns the targets of the DNAME records for the given name without their trailing
+    /// dot.
+    pub async fn resolve_dname_targets(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<String>, DnsError> {
+        let name = name.as_ref();
+        Ok(self
+            .resolve_dname(name)
+            .await?
+            .iter()
+            .map(|a| a.data.trim().trim_end_matches('.').to_string())
+            .collect())
+    }
+
+    /// Returns the answers of the given record type for the given name along with the header
+    /// flags of the response, such as whether it was truncated. The record type is given by
+    /// name such as `a` or `MX`.
+    pub async fn resolve_with_flags(
+        &self,
+        name: impl AsRef<str>,
+        rtype: &str,
+    ) -> Result<DnsReply, DnsError> {
+        let name = name.as_ref();
+        let rtype = match rtype_from_name(rtype) {
+            Some(rtype) => rtype,
+            None => return Err(DnsError::InvalidRecordType),
+        };
+        let res = self.client_request(name, rtype, None).await;
+        let reply = build_reply(res?, rtype);
+        match reply.rcode() {
+            RCode::NoError => Ok(reply),
+            code => Err(DnsError::Status(code)),
+        }
+    }
+
+    /// Returns the answers of the given record type for the given name along with the status
+    /// and header flags of the response like [Dns::resolve_with_flags], but without failing
+    /// on an error status. Some servers return partial answers along with a status such as
+    /// `ServFail`, which can then be used on a best-effort basis.
+    pub async fn resolve_best_effort(
+        &self,
+        name: impl AsRef<str>,
+        rtype: &str,
+    ) -> Result<DnsReply, DnsError> {
+        let name = name.as_ref();
+        let rtype = match rtype_from_name(rtype) {
+            Some(rtype) => rtype,
+            None => return Err(DnsError::InvalidRecordType),
+        };
+        let res = self.client_request(name, rtype, None).await;
+        Ok(build_reply(res?, rtype))
+    }
+
+    /// Resolves the given record type for the given name, trying first the server at the
+    /// index the policy picks among the servers of this instance. The record type is given
+    /// by name such as `a` or `MX`. The other servers follow in their usual order, and an
+    /// index out of range is ignored. This allows custom selection, such as by location or
+    /// cost, without a [Strategy]. The query is otherwise handled like those of the
+    /// `resolve_` methods of each record type, such as [Dns::resolve_a], including local
+    /// names and the cache.
+    pub async fn resolve_with_policy(
+        &self,
+        name: impl AsRef<str>,
+        rtype: &str,
+        policy: impl Fn(&[S]) -> usize,
+    ) -> Result<Vec<DnsAnswer>, DnsError> {
+        let rtype = match rtype_from_name(rtype) {
+            Some(rtype) => rtype,
+            None => return Err(DnsError::InvalidRecordType),
+        };
+        let first = policy(&self.servers);
+        self.request_and_process(name.as_ref(), rtype, Some(first))
+            .await
+    }
+
+    /// Sends the query for the given record type to all servers at once and returns the
+    /// answers of the first server to respond along with how long it took and the URI of
+    /// that server. The record type is given by name such as `a` or `MX`.
+    pub async fn resolve_fastest(
+        &self,
+        name: impl AsRef<str>,
+        rtype: &str,
+    ) -> Result<(Vec<DnsAnswer>, Duration, String), DnsError> {
+        let name = name.as_ref();
+        let rtype = match rtype_from_name(rtype) {
+            Some(rtype) => rtype,
+            None => return Err(DnsError::InvalidRecordType),
+        };
+        if let Some(res) = self.local_response(name, rtype) {
+            return process_response(res?, rtype)
+                .map(|a| (a, Duration::from_secs(0), String::new()));
+        }
+        let name = encode_name(name, &self.idna).map_err(DnsError::Query)?;
+        let name = name.as_str();
+        Counters::add(&self.counters.queries, 1);
+        let start = Instant::now();
+        let mut requests = self
+            .servers
+            .iter()
+            .map(|server| async move {
+                let res = self.retried_server_request(server, name, rtype).await;
+                (res, start.elapsed(), server.uri())
+            })
+            .collect::<FuturesUnordered<_>>();
+        let mut error = QueryError::Unknown;
+        let mut failures = 0;
+        let mut empty = None;
+        while let Some((res, elapsed, uri)) = requests.next().await {
+            match res {
+                Ok(res) => {
+                    if self.race_accepts_empty || has_answers(&res, rtype) {
+                        self.counters.query_done(true);
+                        return process_response(res, rtype).map(|a| (a, elapsed, uri.to_string()));
+                    }
+                    empty.get_or_insert((res, elapsed, uri));
+                }
+                Err(RequestError::Retry(e)) | Err(RequestError::Abort(e)) => {
+                    failures += 1;
+                    self.log_failure(failures, &e);
+                    error = e;
+                }
+            }
+        }
+        if let Some((res, elapsed, uri)) = empty {
+            self.counters.query_done(true);
+            return process_response(res, rtype).map(|a| (a, elapsed, uri.to_string()));
+        }
+        self.counters.query_done(false);
+        Err(DnsError::Query(error))
+    }
+
+    /// Sends the query for the given record type to all servers at once and returns the
+    /// answers of every server that responded, each with the URI of its server. The record
+    /// type is given by name such as `a` or `MX`. The answers are grouped by server in the
+    /// order the servers were given, and the same record returned by several servers is
+    /// returned once for each. This allows attributing the differences between servers. An
+    /// error is only returned if no server gave answers, such as when the name does not
+    /// exist. Names set with [Dns::with_local_names] are not sent to the servers, and their
+    /// local answers are returned with an empty server URI.
+    pub async fn resolve_attributed(
+        &self,
+        name: impl AsRef<str>,
+        rtype: &str,
+    ) -> Result<Vec<AttributedAnswer>, DnsError> {
+        let rtype = match rtype_from_name(rtype) {
+            Some(rtype) => rtype,
+            None => return Err(DnsError::InvalidRecordType),
+        };
+        if let Some(res) = self.local_response(name.as_ref(), rtype) {
+            return Ok(process_response(res?, rtype)?
+                .into_iter()
+                .map(|answer| AttributedAnswer {
+                    answer,
+                    server: String::new(),
+                })
+                .collect());
+        }
+        let name = &encode_name(name.as_ref(), &self.idna).map_err(DnsError::Query)?;
+        Counters::add(&self.counters.queries, 1);
+        let responses = join_all(self.servers.iter().map(|server| async move {
+            let res = self.retried_server_request(server, name, rtype).await;
+            (res, server.uri())
+        }))
+        .await;
+        let mut answers = Vec::new();
+        let mut error = None;
+        let mut answered = false;
+        let mut failures = 0;
+        for (res, uri) in responses {
+            let res = match res {
+                Ok(res) => process_response(res, rtype),
+                Err(RequestError::Retry(e)) | Err(RequestError::Abort(e)) => {
+                    failures += 1;
+                    self.log_failure(failures, &e);
+                    Err(DnsError::Query(e))
+                }
+            };
+            match res {
+                Ok(server_answers) => {
+                    answered = true;
+                    answers.extend(server_answers.into_iter().map(|answer| AttributedAnswer {
+                        answer,
+                        server: uri.to_string(),
+                    }));
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+        self.counters.query_done(failures < self.servers.len());
+        match error {
+            Some(e) if !answered => Err(e),
+            _ => Ok(answers),
+        }
+    }
+
+    /// Returns the JSON response of the servers to a query for the given name and record type
+    /// as it was received, without filtering its answers or dropping fields that are not
+    /// parsed otherwise, such as `Question`. The record type is given by name such as `a` or
+    /// `MX`. The servers are tried in the given order and an error status such as
+    /// `NXDomain` is returned in the response rather than as an error. This allows proxying
+    /// the responses. Responses are not cached.
+    pub async fn resolve_json(
+        &self,
+        name: impl AsRef<str>,
+        rtype: &str,
+    ) -> Result<serde_json::Value, DnsError> {
+        let name = name.as_ref();
+        let rtype = match rtype_from_name(rtype) {
+            Some(rtype) => rtype,
+            None => return Err(DnsError::InvalidRecordType),
+        };
+        if let Some(res) = self.local_response(name, rtype) {
+            return serde_json::to_value(res?)
+                .map_err(|e| DnsError::Query(QueryError::ParseResponse(e.to_string())));
+        }
+        let name = encode_name(name, &self.idna).map_err(DnsError::Query)?;
+        Counters::add(&self.counters.queries, 1);
+        let parse = |body: &[u8]| {
+            serde_json::from_slice::<serde_json::Value>(body)
+                .map_err(|e| QueryError::ParseResponse(e.to_string()))
+        };
+        let mut error = QueryError::Unknown;
+        for (i, server) in self.servers.iter().enumerate() {
+            match self.server_request_with(server, &name, rtype, parse).await {
+                Ok(value) => {
+                    self.counters.query_done(true);
+                    return Ok(value);
+                }
+                Err(RequestError::Retry(e)) => {
+                    self.log_failure(i + 1, &e);
+                    error = e;
+                }
+                Err(RequestError::Abort(e)) => {
+                    self.log_failure(i + 1, &e);
+                    error = e;
+                    break;
+                }
+            }
+        }
+        self.counters.query_done(false);
+        Err(DnsError::Query(error))
+    }
+
+    /// Returns the PTR records for a reverse name such as `34.216.184.93.in-addr.arpa` after
+    /// checking that it is a complete `in-addr.arpa` or `ip6.arpa` name. A trailing dot and
+    /// any letter case are accepted. Malformed names return a [QueryError::InvalidName]
+    /// error describing the problem.
+    pub async fn resolve_arpa_ptr(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<DnsAnswer>, DnsError> {
+        let name = validate_arpa(name.as_ref()).map_err(DnsError::Query)?;
+        self.resolve_ptr(name).await
+    }
+
+    /// Returns the PTR records for an IP address given as a string such as `93.184.216.34` or
+    /// `2001:db8::1`, by querying its `in-addr.arpa` or `ip6.arpa` name. An invalid address
+    /// returns a [QueryError::InvalidName] error.
+    pub async fn resolve_ptr_for_ip_str(&self, ip: &str) -> Result<Vec<DnsAnswer>, DnsError> {
+        let ip = ip
+            .trim()
+            .parse::<IpAddr>()
+            .map_err(|e| DnsError::Query(QueryError::InvalidName(format!("{}: {}", ip, e))))?;
+        self.resolve_ptr(reverse_name(ip)).await
+    }
+
+    /// Returns the URL that is requested from the given server when querying the given name
+    /// and record type, without making any request. The record type is given by name such as
+    /// `a` or `MX`. This allows testing how queries are built.
+    pub fn build_query_url(
+        &self,
+        server: &S,
+        name: impl AsRef<str>,
+        rtype: &str,
+    ) -> Result<String, DnsError> {
+        let rtype = match rtype_from_name(rtype) {
+            Some(rtype) => rtype,
+            None => return Err(DnsError::InvalidRecordType),
+        };
+        let name = encode_name(name.as_ref(), &self.idna).map_err(DnsError::Query)?;
+        Ok(self.server_url(server, &name, rtype))
+    }
+
+    /// Queries the given record type for the given name on two servers and returns the
+    /// answers they have in common and those only one of them returned. The record type is
+    /// given by name such as `a` or `MX`. The servers need not be among the servers of this
+    /// instance. This helps detect tampering or split-horizon setups. Names set with
+    /// [Dns::with_local_names] are not sent to the servers, and their local answers are
+    /// returned as common to both.
+    pub async fn compare(
+        &self,
+        name: impl AsRef<str>,
+        rtype: &str,
+        server_a: &S,
+        server_b: &S,
+    ) -> Result<AnswerDiff, DnsError> {
+        let rtype = match rtype_from_name(rtype) {
+            Some(rtype) => rtype,
+            None => return Err(DnsError::InvalidRecordType),
+        };
+        if let Some(res) = self.local_response(name.as_ref(), rtype) {
+            return Ok(AnswerDiff {
+                common: process_response(res?, rtype)?,
+                only_a: Vec::new(),
+                only_b: Vec::new(),
+            });
+        }
+        let name = &encode_name(name.as_ref(), &self.idna).map_err(DnsError::Query)?;
+        let request = |server| async move {
+            match self.retried_server_request(server, name, rtype).await {
+                Ok(res) => process_response(res, rtype),
+                Err(RequestError::Retry(e)) | Err(RequestError::Abort(e)) => {
+                    self.log_failure(1, &e);
+                    Err(DnsError::Query(e))
+                }
+            }
+        };
+        let (a, b) = try_join!(request(server_a), request(server_b))?;
+        let key = |a: &DnsAnswer| {
+            (
+                a.name.trim_end_matches('.').to_ascii_lowercase(),
+                a.r#type,
+                a.data.clone(),
+            )
+        };
+        let keys_a = a.iter().map(key).collect::<Vec<_>>();
+        let keys_b = b.iter().map(key).collect::<Vec<_>>();
+        let (common, only_a) = a
+            .into_iter()
+            .zip(keys_a.iter())
+            .partition::<Vec<_>, _>(|(_, k)| keys_b.contains(k));
+        Ok(AnswerDiff {
+            common: common.into_iter().map(|(a, _)| a).collect(),
+            only_a: only_a.into_iter().map(|(a, _)| a).collect(),
+            only_b: b
+                .into_iter()
+                .zip(keys_b.iter())
+                .filter(|(_, k)| !keys_a.contains(k))
+                .map(|(b, _)| b)
+                .collect(),
+        })
+    }
+
+    /// Returns the answers to an `ANY` query for the given name grouped by their record type.
+    /// The keys can be named with [Dns::rtype_to_name].
+    pub async fn resolve_any_grouped(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<HashMap<u32, Vec<DnsAnswer>>, DnsError> {
+        let mut groups = HashMap::new();
+        for answer in self.resolve_any(name).await? {
+            groups
+                .entry(answer.r#type)
+                .or_insert_with(Vec::new)
+                .push(answer);
+        }
+        Ok(groups)
+    }
+
+    /// Inserts the given answers into the cache as the response to the given name and record
+    /// type, without making any request. The record type is given by name such as `a` or
+    /// `MX`. The entry expires after `ttl_override` if given, or otherwise after the lowest TTL
+    /// of the answers within the bounds set with [Dns::with_cache_ttl_bounds]. This allows
+    /// preloading records from a snapshot or pinning critical records. It has no effect if the
+    /// cache is disabled.
+    pub fn prime_cache(
+        &self,
+        name: impl AsRef<str>,
+        rtype: &str,
+        answers: Vec<DnsAnswer>,
+        ttl_override: Option<Duration>,
+    ) -> Result<(), DnsError> {
+        let rtype = match rtype_from_name(rtype) {
+            Some(rtype) => rtype,
+            None => return Err(DnsError::InvalidRecordType),
+        };
+        let name = encode_name(name.as_ref(), &self.idna).map_err(DnsError::Query)?;
+        let cache = match self.cache {
+            Some(ref cache) => cache,
+            None => return Ok(()),
+        };
+        let response = DnsResponse {
+            Status: RCode::NoError as u32,
+            TC: false,
+            RD: true,
+            RA: true,
+            AD: false,
+            CD: false,
+            Answer: Some(answers),
+            Authority: None,
+            Comment: None,
+        };
+        let key = (name.to_ascii_lowercase(), rtype.0);
+        match ttl_override {
+            Some(ttl) => cache.insert_for(key, &response, ttl),
+            None => cache.insert(
+                key,
+                &response,
+                self.cache_ttl_bounds,
+                &mut *self.rng.lock().unwrap(),
+            ),
+        }
+        Ok(())
+    }
+
+    /// Resolves the given record type for all the given names concurrently and returns the
+    /// result of each name in the same order. The record type is given by name such as `a` or
+    /// `MX`. Names that are not resolved within the deadline get a [DnsError::Timeout] error
+    /// while the others are returned normally, so that a few slow names cannot stall the
+    /// whole batch. The deadline is enforced with the timer of this instance like server
+    /// timeouts.
+    pub async fn resolve_many<N: AsRef<str>>(
+        &self,
+        names: &[N],
+        rtype: &str,
+        deadline: Duration,
+    ) -> Vec<Result<Vec<DnsAnswer>, DnsError>> {
+        join_all(names.iter().map(|name| async move {
+            timer::timeout(
+                self.timer.as_deref(),
+                deadline,
+                self.resolve_str_type(name, rtype),
+            )
+            .await
+            .unwrap_or(Err(DnsError::Timeout))
+        }))
+        .await
+    }
+
+    /// Resolves each of the given pairs of name and record type and returns the result of
+    /// each query in the same order. Record types are given by name such as `a` or `MX`.
+    /// Unlike [Dns::resolve_many], each query may be of a different type. At most 16 queries
+    /// are resolved at the same time; [Dns::with_max_concurrent] further limits the requests
+    /// sent to the servers.
+    pub async fn resolve_batch(
+        &self,
+        queries: &[(&str, &str)],
+    ) -> Vec<Result<Vec<DnsAnswer>, DnsError>> {
+        futures_util::stream::iter(queries)
+            .map(|&(name, rtype)| self.resolve_str_type(name, rtype))
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Returns the `A` records for the given name along with the TTL of a negative answer.
+    /// The answers are `None` if the name does not exist (`NXDomain`). For `NXDomain` and for
+    /// responses without answers, the negative TTL is the lower of the TTL and the minimum
+    /// field of the SOA record in the authority section, as defined in RFC 2308. It is `None`
+    /// for other responses or if the server did not return an SOA record.
+    pub async fn resolve_a_with_negative_ttl(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<(Option<Vec<DnsAnswer>>, Option<Duration>), DnsError> {
+        let res = self.client_request(name.as_ref(), &RTYPE_a, None).await?;
+        let negative_ttl = res
+            .Authority
+            .as_ref()
+            .and_then(|authority| authority.iter().find(|a| a.r#type == RTYPE_soa.0))
+            .and_then(|soa| SoaRecord::parse(&soa.data).map(|record| record.minimum.min(soa.TTL)))
+            .map(|ttl| Duration::from_secs(u64::from(ttl)));
+        match num::FromPrimitive::from_u32(res.Status) {
+            Some(RCode::NXDomain) => Ok((None, negative_ttl)),
+            Some(RCode::NoError) => {
+                let answers = filter_answers(res.Answer, &RTYPE_a);
+                let negative_ttl = negative_ttl.filter(|_| answers.is_empty());
+                Ok((Some(answers), negative_ttl))
+            }
+            Some(code) => Err(DnsError::Status(code)),
+            None => Err(DnsError::Status(RCode::Unknown)),
+        }
+    }
+
+    /// Returns the answers for the given name and record type along with the name and type
+    /// that were actually queried, such as the ASCII form of an internationalized name. The
+    /// record type is given by name such as `a` or `MX`. This allows logging what was queried
+    /// rather than what was given.
+    pub async fn resolve_issued(
+        &self,
+        name: impl AsRef<str>,
+        rtype: &str,
+    ) -> Result<IssuedQuery, DnsError> {
+        let name = name.as_ref();
+        let rtype = match rtype_from_name(rtype) {
+            Some(rtype) => rtype,
+            None => return Err(DnsError::InvalidRecordType),
+        };
+        let issued_name = encode_name(name, &self.idna).map_err(DnsError::Query)?;
+        let answers = self.request_and_process(name, rtype, None).await?;
+        Ok(IssuedQuery {
+            issued_name,
+            issued_type: rtype.0,
+            answers,
+        })
+    }
+
+    /// Returns the `A` records for the given name along with the time the query took, from
+    /// just before the first server is queried until its response is parsed, including any
+    /// retries. Answers from the cache are returned with the time of the cache lookup.
+    pub async fn resolve_a_timed(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<(Vec<DnsAnswer>, Duration), DnsError> {
+        let start = Instant::now();
+        let answers = self
+            .request_and_process(name.as_ref(), &RTYPE_a, None)
+            .await?;
+        Ok((answers, start.elapsed()))
+    }
+
+    /// Returns whether the given name is a delegated zone such as a registered domain. It is
+    /// the case if it has an SOA record at its apex or NS records. A name that does not exist
+    /// (`NXDomain`) and a name that only exists within another zone, such as
+    /// `www.example.com`, are not delegated.
+    pub async fn domain_exists(&self, name: impl AsRef<str>) -> Result<bool, DnsError> {
+        let name = name.as_ref();
+        let apex = encode_name(name, &self.idna).map_err(DnsError::Query)?;
+        let at_apex = |a: &DnsAnswer| a.name.trim_end_matches('.').eq_ignore_ascii_case(&apex);
+        let res = self.client_request(name, &RTYPE_soa, None).await?;
+        match num::FromPrimitive::from_u32(res.Status) {
+            Some(RCode::NXDomain) => return Ok(false),
+            Some(RCode::NoError) => {}
+            Some(code) => return Err(DnsError::Status(code)),
+            None => return Err(DnsError::Status(RCode::Unknown)),
+        }
+        if filter_answers(res.Answer, &RTYPE_soa).iter().any(at_apex) {
+            return Ok(true);
+        }
+        Ok(self.resolve_ns(name).await?.iter().any(at_apex))
+    }
+
     // Generates the DNS over HTTPS request on the given name for rtype. It filters out
-    // results that are not of the given rtype with the exception of `ANY`.
+    // results that are not of the given rtype with the exception of `ANY`. With the `tracing`
+    // feature, the query runs within a `resolve` span. The server at index `first` is tried
+    // first if given.
     async fn request_and_process(
         &self,
         name: &str,
         rtype: &Rtype,
+        first: Option<usize>,
     ) -> Result<Vec<DnsAnswer>, DnsError> {
-        match self.client_request(name, rtype).await {
-            Err(e) => Err(DnsError::Query(e)),
-            Ok(res) => match num::FromPrimitive::from_u32(res.Status) {
-                Some(RCode::NoError) => Ok(res
-                    .Answer
-                    .unwrap_or_default()
-                    .into_iter()
-                    // Get only the record types requested. There is only exception and that is
-                    // the ANY record which has a value of 0.
-                    .filter(|a| a.r#type == rtype.0 || rtype.0 == 0)
-                    .collect::<Vec<_>>()),
-                Some(code) => Err(DnsError::Status(code)),
-                None => Err(DnsError::Status(RCode::Unknown)),
+        let request = self.request_and_process_untraced(name, rtype, first);
+        #[cfg(feature = "tracing")]
+        let request = crate::trace::traced(
+            tracing::debug_span!(
+                "resolve",
+                name,
+                rtype = rtype.1,
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            ),
+            request,
+            |res| match res {
+                Ok(_) => "ok".to_string(),
+                Err(e) => e.to_string(),
             },
+        );
+        request.await
+    }
+
+    // Makes the query of `request_and_process` without a span.
+    async fn request_and_process_untraced(
+        &self,
+        name: &str,
+        rtype: &Rtype,
+        first: Option<usize>,
+    ) -> Result<Vec<DnsAnswer>, DnsError> {
+        let mut res = self.client_request(name, rtype, first).await?;
+        self.drop_unrelated(name, &mut res);
+        let cnames = match rtype.0 {
+            0 | 5 => Vec::new(),
+            _ if self.keep_cnames => filter_answers(res.Answer.clone(), &RTYPE_cname),
+            _ => Vec::new(),
+        };
+        let mut answers = process_response(res, rtype)?;
+        // The limit applies to the answers of the requested type only, so that a long alias
+        // chain cannot crowd them out.
+        if let Some(max) = self.max_answers {
+            answers.truncate(max);
+        }
+        if !cnames.is_empty() {
+            answers.splice(0..0, cnames);
         }
+        let answers = match self.transform {
+            Some(ref transform) => transform(name, rtype.0, answers),
+            None => answers,
+        };
+        if self.empty_as_error && answers.is_empty() {
+            return Err(DnsError::NoAnswers);
+        }
+        Ok(answers)
     }
 
-    // Creates the HTTPS request to the server. In certain occasions, it retries to a new server
-    // if one is available.
-    async fn client_request(&self, name: &str, rtype: &Rtype) -> Result<DnsResponse, QueryError> {
-        // Name has to be puny encoded.
-        let name = match idna::domain_to_ascii(name) {
-            Ok(name) => name,
-            Err(e) => return Err(QueryError::InvalidName(format!("{:?}", e))),
+    // Removes the answers whose name is neither the queried name nor a target of the CNAME
+    // chain starting from it, if set with `Dns::with_strict_name_match`.
+    fn drop_unrelated(&self, name: &str, res: &mut DnsResponse) {
+        let answers = match res.Answer {
+            Some(ref mut answers) if self.strict_name_match => answers,
+            _ => return,
         };
-        let mut error = QueryError::Unknown;
-        for server in self.servers.iter() {
-            let url = format!("{}?name={}&type={}", server.uri(), name, rtype.1);
-            let endpoint = match url.parse::<Uri>() {
-                Err(e) => return Err(QueryError::InvalidEndpoint(e.to_string())),
-                Ok(endpoint) => endpoint,
-            };
+        let normalize = |name: &str| name.trim_end_matches('.').to_ascii_lowercase();
+        let mut related = match encode_name(name, &self.idna) {
+            Ok(name) => vec![normalize(&name)],
+            Err(_) => return,
+        };
+        // The chain is followed in any order of the answers, as long as it grows.
+        let mut grown = true;
+        while grown {
+            grown = false;
+            for answer in answers.iter().filter(|a| a.r#type == RTYPE_cname.0) {
+                let target = normalize(&answer.data);
+                if related.contains(&normalize(&answer.name)) && !related.contains(&target) {
+                    related.push(target);
+                    grown = true;
+                }
+            }
+        }
+        answers.retain(|a| related.contains(&normalize(&a.name)));
+    }
+
+    // Answers the query locally if the name is bypassed, or otherwise creates the HTTPS
+    // request to the server.
+    async fn client_request(
+        &self,
+        name: &str,
+        rtype: &Rtype,
+        first: Option<usize>,
+    ) -> Result<DnsResponse, DnsError> {
+        if let Some(res) = self.local_response(name, rtype) {
+            return res;
+        }
+        self.cached_request(name, rtype, first)
+            .await
+            .map_err(DnsError::Query)
+    }
+
+    // Returns the response to a query for a name in the bypass list. Names under `localhost`
+    // resolve to the loopback addresses and others return a [DnsError::LocalName] error.
+    fn local_response(&self, name: &str, rtype: &Rtype) -> Option<Result<DnsResponse, DnsError>> {
+        let name = name.trim();
+        let name = name.strip_suffix('.').unwrap_or(name).to_ascii_lowercase();
+        let is_under = |name: &str, domain: &str| {
+            name.strip_suffix(domain)
+                .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('.'))
+        };
+        if !self
+            .local_names
+            .iter()
+            .any(|local| match local.strip_prefix('.') {
+                Some(suffix) => name.len() > suffix.len() && is_under(&name, suffix),
+                None => is_under(&name, local),
+            })
+        {
+            return None;
+        }
+        if !is_under(&name, "localhost") {
+            return Some(Err(DnsError::LocalName(name)));
+        }
+        let loopback = match rtype.0 {
+            1 => vec!["127.0.0.1"],
+            28 => vec!["::1"],
+            0 => vec!["127.0.0.1", "::1"],
+            _ => Vec::new(),
+        };
+        let answers = loopback
+            .into_iter()
+            .map(|ip| DnsAnswer {
+                name: format!("{}.", name),
+                r#type: if ip.contains(':') {
+                    RTYPE_aaaa.0
+                } else {
+                    RTYPE_a.0
+                },
+                TTL: 0,
+                data: ip.to_string(),
+            })
+            .collect();
+        Some(Ok(DnsResponse {
+            Status: RCode::NoError as u32,
+            TC: false,
+            RD: true,
+            RA: true,
+            AD: false,
+            CD: false,
+            Answer: Some(answers),
+            Authority: None,
+            Comment: None,
+        }))
+    }
+
+    // Creates the HTTPS request to the server, sharing it with identical queries in flight if
+    // enabled.
+    async fn cached_request(
+        &self,
+        name: &str,
+        rtype: &Rtype,
+        first: Option<usize>,
+    ) -> Result<DnsResponse, QueryError> {
+        let name = encode_name(name, &self.idna)?;
+        let key = (name.to_ascii_lowercase(), rtype.0);
+        if let Some(ref cache) = self.cache {
+            match cache.get(&key) {
+                Some(res) => {
+                    Counters::add(&self.counters.cache_hits, 1);
+                    return Ok(res);
+                }
+                None => Counters::add(&self.counters.cache_misses, 1),
+            }
+        }
+        let res = match self.single_flight {
+            Some(ref single_flight) => {
+                let (res, shared) = single_flight
+                    .run(key.clone(), || self.servers_request(&name, rtype, first))
+                    .await;
+                if shared {
+                    Counters::add(&self.counters.coalesced, 1);
+                }
+                res
+            }
+            None => self.servers_request(&name, rtype, first).await,
+        };
+        if let (Some(cache), Ok(res)) = (self.cache.as_ref(), res.as_ref()) {
+            let ephemeral = res.Answer.as_ref().is_some_and(|answers| {
+                answers
+                    .iter()
+                    .any(|a| Duration::from_secs(u64::from(a.TTL)) < self.min_cacheable_ttl)
+            });
+            if !ephemeral {
+                cache.insert(
+                    key,
+                    res,
+                    self.cache_ttl_bounds,
+                    &mut *self.rng.lock().unwrap(),
+                );
+            }
+        }
+        res
+    }
+
+    // Creates the HTTPS request to the server for a name that is already encoded. In certain
+    // occasions, it retries to a new server if one is available.
+    async fn servers_request(
+        &self,
+        name: &str,
+        rtype: &Rtype,
+        first: Option<usize>,
+    ) -> Result<DnsResponse, QueryError> {
+        Counters::add(&self.counters.queries, 1);
+        let res = self.fallback_request(name, rtype, first).await;
+        self.counters.query_done(res.is_ok());
+        #[cfg(feature = "system-fallback")]
+        if res.is_err() && self.system_fallback {
+            if let Some(res) = system_lookup(name, rtype).await {
+                return Ok(res);
+            }
+        }
+        res
+    }
+
+    // Tries each server in order until one gives a response that ends the query. The server
+    // at index `first` is tried first if given, before the sticky server.
+    async fn fallback_request(
+        &self,
+        name: &str,
+        rtype: &Rtype,
+        first: Option<usize>,
+    ) -> Result<DnsResponse, QueryError> {
+        let mut errors = Vec::new();
+        let mut response = None;
+        let mut order = self.strategy.order(
+            self.servers.len(),
+            &self.latencies,
+            &mut *self.rng.lock().unwrap(),
+        );
+        if let Some(ref sticky) = self.sticky_server {
+            let last = sticky.load(Ordering::Relaxed);
+            if let Some(pos) = order.iter().position(|&i| i == last) {
+                order[..=pos].rotate_right(1);
+            }
+        }
+        if let Some(pos) = first.and_then(|first| order.iter().position(|&i| i == first)) {
+            order[..=pos].rotate_right(1);
+        }
+        if let Some(ref breaker) = self.breaker {
+            let closed = order
+                .iter()
+                .copied()
+                .filter(|&i| !breaker.is_open(i))
+                .collect::<Vec<_>>();
+            if !closed.is_empty() {
+                order = closed;
+            }
+        }
+        for i in order {
+            let server = &self.servers[i];
+            if !errors.is_empty() {
+                Counters::add(&self.counters.retries, 1);
+            }
+            let start = Instant::now();
+            let res = self.retried_server_request(server, name, rtype).await;
+            self.latencies.record(
+                i,
+                match res {
+                    Ok(_) => start.elapsed(),
+                    Err(_) => self.server_timeout(server),
+                },
+            );
+            if let Some(ref breaker) = self.breaker {
+                match res {
+                    Ok(_) => breaker.record(i, true, self.breaker_window),
+                    Err(RequestError::Retry(_)) => breaker.record(i, false, self.breaker_window),
+                    // The request itself was at fault, not the server.
+                    Err(RequestError::Abort(_)) => (),
+                }
+            }
+            if let (Some(sticky), Ok(_)) = (self.sticky_server.as_ref(), res.as_ref()) {
+                sticky.store(i, Ordering::Relaxed);
+            }
+            match res {
+                Ok(res) if self.stops_on(&res) => return Ok(res),
+                Ok(res) => response = Some(res),
+                Err(RequestError::Abort(e)) => {
+                    self.log_failure(errors.len() + 1, &e);
+                    errors.push((server.uri().to_string(), e));
+                    break;
+                }
+                Err(RequestError::Retry(e)) => {
+                    self.log_failure(errors.len() + 1, &e);
+                    errors.push((server.uri().to_string(), e));
+                }
+            }
+        }
+        // A response that does not stop the query is still better than an error.
+        if let Some(res) = response {
+            return Ok(res);
+        }
+        if let Some(ref on_exhausted) = self.on_exhausted {
+            on_exhausted(name, &errors);
+        }
+        Err(errors.pop().map_or(QueryError::Unknown, |(_, e)| e))
+    }
+
+    // Whether the response ends the query without trying the next server.
+    fn stops_on(&self, res: &DnsResponse) -> bool {
+        let stop_on = match self.stop_on {
+            Some(ref stop_on) => stop_on,
+            None => return true,
+        };
+        let rcode = num::FromPrimitive::from_u32(res.Status).unwrap_or(RCode::Unknown);
+        match rcode {
+            RCode::NoError if res.Answer.as_ref().is_some_and(|a| !a.is_empty()) => true,
+            rcode => stop_on.contains(&rcode),
+        }
+    }
+
+    // Makes a request to the given server, retrying it with an exponential backoff as many
+    // times as configured if the error is retryable. `ANY` queries are emulated on servers
+    // that do not support them.
+    async fn retried_server_request(
+        &self,
+        server: &S,
+        name: &str,
+        rtype: &Rtype,
+    ) -> Result<DnsResponse, RequestError> {
+        if rtype.0 == 0 && !server.supports_any() {
+            return self.emulated_any_request(server, name).await;
+        }
+        self.retried_type_request(server, name, rtype).await
+    }
+
+    // Answers an `ANY` query on a server that does not support it by querying the common
+    // record types on it at once and merging their answers into a single response. The first
+    // error, or the first response with an error status, is returned instead if there is one.
+    async fn emulated_any_request(
+        &self,
+        server: &S,
+        name: &str,
+    ) -> Result<DnsResponse, RequestError> {
+        let rtypes = [
+            &RTYPE_a,
+            &RTYPE_aaaa,
+            &RTYPE_mx,
+            &RTYPE_txt,
+            &RTYPE_ns,
+            &RTYPE_soa,
+            &RTYPE_cname,
+        ];
+        let responses = join_all(
+            rtypes
+                .iter()
+                .map(|&rtype| self.retried_type_request(server, name, rtype)),
+        )
+        .await;
+        let mut responses = responses.into_iter().collect::<Result<Vec<_>, _>>()?;
+        if let Some(pos) = responses
+            .iter()
+            .position(|res| res.Status != RCode::NoError as u32)
+        {
+            return Ok(responses.swap_remove(pos));
+        }
+        let answers = responses
+            .iter_mut()
+            .zip(rtypes)
+            .flat_map(|(res, rtype)| filter_answers(res.Answer.take(), rtype))
+            .collect();
+        let authenticated = responses.iter().all(|res| res.AD);
+        let mut res = responses.swap_remove(0);
+        res.AD = authenticated;
+        res.Answer = Some(answers);
+        Ok(res)
+    }
+
+    // Makes a request like `retried_server_request` without emulating `ANY` queries.
+    async fn retried_type_request(
+        &self,
+        server: &S,
+        name: &str,
+        rtype: &Rtype,
+    ) -> Result<DnsResponse, RequestError> {
+        let mut attempt = 0;
+        loop {
+            match self.server_request(server, name, rtype).await {
+                Err(RequestError::Retry(e)) if attempt < self.per_server_retries => {
+                    Counters::add(&self.counters.retries, 1);
+                    // The delay asked by the server is honored up to the server timeout.
+                    let backoff = RETRY_BACKOFF * 2u32.saturating_pow(attempt);
+                    let delay = match e.retry_after() {
+                        Some(delay) => delay.min(self.server_timeout(server)).max(backoff),
+                        None => backoff,
+                    };
+                    if let Some(timer) = self.timer.as_deref() {
+                        timer.sleep(delay).await;
+                    }
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
 
-            error = match timeout(server.timeout(), self.client.get(endpoint)).await {
-                Ok(Err(e)) => QueryError::Connection(e.to_string()),
-                Ok(Ok(res)) => {
+    // Makes a single request to the given server for a name that is already encoded.
+    async fn server_request(
+        &self,
+        server: &S,
+        name: &str,
+        rtype: &Rtype,
+    ) -> Result<DnsResponse, RequestError> {
+        self.server_request_with(server, name, rtype, |body| self.parse_response(body))
+            .await
+    }
+
+    // Makes a single request like `server_request` but parses the body of the response with
+    // the given function. With the `tracing` feature, the request runs within a
+    // `server_attempt` span.
+    async fn server_request_with<T>(
+        &self,
+        server: &S,
+        name: &str,
+        rtype: &Rtype,
+        parse: impl Fn(&[u8]) -> Result<T, QueryError>,
+    ) -> Result<T, RequestError> {
+        let request = self.server_request_untraced(server, name, rtype, parse);
+        #[cfg(feature = "tracing")]
+        let request = crate::trace::traced(
+            tracing::debug_span!(
+                "server_attempt",
+                server = server.uri(),
+                rtype = rtype.1,
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            ),
+            request,
+            |res| match res {
+                Ok(_) => "ok".to_string(),
+                Err(RequestError::Retry(e)) | Err(RequestError::Abort(e)) => e.to_string(),
+            },
+        );
+        request.await
+    }
+
+    // Makes the request of `server_request_with` without a span.
+    async fn server_request_untraced<T>(
+        &self,
+        server: &S,
+        name: &str,
+        rtype: &Rtype,
+        parse: impl Fn(&[u8]) -> Result<T, QueryError>,
+    ) -> Result<T, RequestError> {
+        if self.json_post {
+            let url = server.uri().to_string();
+            let body = self.query_params(server, name, rtype);
+            return self
+                .url_request(server, &url, Some(body), &parse)
+                .await
+                .map_err(|e| e.map(|e| QueryError::AtUrl(url.clone(), Box::new(e))));
+        }
+        let url = self.server_url(server, name, rtype);
+        // The URL of the next server may be short enough.
+        if url.len() > MAX_URL_LENGTH {
+            let error = QueryError::UrlTooLong(url.len());
+            return Err(RequestError::Retry(QueryError::AtUrl(url, Box::new(error))));
+        }
+        // Attach the URL to any error so it can be reproduced.
+        self.url_request(server, &url, None, &parse)
+            .await
+            .map_err(|e| e.map(|e| QueryError::AtUrl(url.clone(), Box::new(e))))
+    }
+
+    // Returns the timeout of the given server, or the default timeout if it has none.
+    fn server_timeout(&self, server: &S) -> Duration {
+        match server.timeout() {
+            timeout if timeout == Duration::from_secs(0) => self.default_timeout,
+            timeout => timeout,
+        }
+    }
+
+    // Logs the error of a server on which a query failed once the query has failed on as many
+    // servers as set with `Dns::with_log_after_failures`.
+    fn log_failure(&self, failures: usize, error: &QueryError) {
+        if failures >= self.log_after_failures {
+            error!("request error: {}", error);
+        }
+    }
+
+    // Returns the URL of the query to the given server for a name that is already encoded.
+    // The URL is built in a single buffer sized for the common case so that a query
+    // allocates once for it.
+    fn server_url(&self, server: &S, name: &str, rtype: &Rtype) -> String {
+        let uri = server.uri();
+        let mut url = String::with_capacity(uri.len() + name.len() + 32);
+        url.push_str(uri);
+        url.push_str(query_separator(uri));
+        self.push_query_params(&mut url, server, name, rtype);
+        url
+    }
+
+    // Returns the query parameters for the given server for a name that is already encoded.
+    fn query_params(&self, server: &S, name: &str, rtype: &Rtype) -> String {
+        let mut params = String::with_capacity(name.len() + 32);
+        self.push_query_params(&mut params, server, name, rtype);
+        params
+    }
+
+    // Appends the query parameters for the given server to the buffer without intermediate
+    // allocations.
+    fn push_query_params(&self, buf: &mut String, server: &S, name: &str, rtype: &Rtype) {
+        // Writing to a `String` cannot fail.
+        let _ = write!(buf, "name={}&type={}", name, rtype.1);
+        if let (Some(size), Some(param)) = (self.edns_buffer_size, server.edns_buffer_size_param())
+        {
+            let _ = write!(buf, "&{}={}", param, size);
+        }
+        if !self.flags.recursion_desired {
+            buf.push_str("&rd=0");
+        }
+        if self.flags.checking_disabled {
+            buf.push_str("&cd=1");
+        }
+        if self.flags.dnssec_ok {
+            buf.push_str("&do=1");
+        }
+        if self.flags.authenticated_data {
+            buf.push_str("&ad=1");
+        }
+        for (name, value) in server.extra_query_params() {
+            let _ = write!(buf, "&{}={}", name, value);
+        }
+    }
+
+    // Makes a single request to the given server at the given URL and parses the body of the
+    // response with the given function. The request is a `POST` with the given form encoded
+    // body if there is one.
+    async fn url_request<T>(
+        &self,
+        server: &S,
+        url: &str,
+        body: Option<String>,
+        parse: &impl Fn(&[u8]) -> Result<T, QueryError>,
+    ) -> Result<T, RequestError> {
+        // The permit is held until the response is read.
+        #[cfg(feature = "tokio")]
+        let _permit = match self.concurrency {
+            Some(ref semaphore) => Some(semaphore.acquire().await.expect("semaphore is open")),
+            None => None,
+        };
+        Counters::add(&self.counters.requests, 1);
+        let endpoint = match url.parse::<Uri>() {
+            Err(e) => {
+                return Err(RequestError::Abort(QueryError::InvalidEndpoint(
+                    e.to_string(),
+                )))
+            }
+            Ok(endpoint) => endpoint,
+        };
+
+        let send = |endpoint: Uri| match body {
+            Some(ref body) => self
+                .client
+                .post_with_headers(endpoint, body.clone(), &self.headers),
+            None => self.client.get_with_headers(endpoint, &self.headers),
+        };
+        let request = async {
+            let mut endpoint = endpoint;
+            let mut visited = Vec::new();
+            loop {
+                let res = match send(endpoint.clone()).await {
+                    // A pooled connection that was dropped by the server while idle fails the
+                    // first request made on it. The broken connection is removed from the pool
+                    // so the request is sent once more on a new one.
+                    Err(e) if e.is_closed() || e.is_incomplete_message() || e.is_canceled() => {
+                        Counters::add(&self.counters.requests, 1);
+                        Counters::add(&self.counters.retries, 1);
+                        send(endpoint.clone()).await?
+                    }
+                    res => res?,
+                };
+                // Redirect loops are cut short by never revisiting a URL.
+                match redirect_location(&res) {
+                    Some(location)
+                        if visited.len() < self.max_redirects
+                            && location != endpoint
+                            && !visited.contains(&location) =>
+                    {
+                        Counters::add(&self.counters.requests, 1);
+                        visited.push(std::mem::replace(&mut endpoint, location));
+                    }
+                    _ => return hyper::Result::Ok(res),
+                }
+            }
+        };
+        let error =
+            match timer::timeout(self.timer.as_deref(), self.server_timeout(server), request).await
+            {
+                Some(Err(e)) => QueryError::Connection(e.to_string()),
+                Some(Ok(res)) => {
                     match res.status().as_u16() {
-                        200 => match hyper::body::to_bytes(res).await {
-                            Err(e) => QueryError::ReadResponse(e.to_string()),
-                            Ok(body) => match serde_json::from_slice::<DnsResponse>(&body) {
-                                Err(e) => QueryError::ParseResponse(e.to_string()),
-                                Ok(res) => {
-                                    return Ok(res);
+                        200 => {
+                            let encoding = res
+                                .headers()
+                                .get(CONTENT_ENCODING)
+                                .and_then(|v| v.to_str().ok())
+                                .map(|v| v.trim().to_ascii_lowercase());
+                            match hyper::body::to_bytes(res).await {
+                                Err(e) => QueryError::ReadResponse(e.to_string()),
+                                Ok(body) => {
+                                    Counters::add(&self.counters.bytes_received, body.len() as u64);
+                                    match decode_body(encoding.as_deref(), &body) {
+                                        Err(e) => e,
+                                        Ok(body) => match parse(&body) {
+                                            Err(e) => e,
+                                            Ok(res) => {
+                                                return Ok(res);
+                                            }
+                                        },
+                                    }
                                 }
-                            },
-                        },
-                        400 => return Err(QueryError::BadRequest400),
-                        413 => return Err(QueryError::PayloadTooLarge413),
-                        414 => return Err(QueryError::UriTooLong414),
-                        415 => return Err(QueryError::UnsupportedMediaType415),
-                        501 => return Err(QueryError::NotImplemented501),
-                        // If the following errors occur, the request will be retried on
-                        // the next server if one is available.
-                        429 => QueryError::TooManyRequests429,
-                        500 => QueryError::InternalServerError500,
-                        502 => QueryError::BadGateway502,
-                        504 => QueryError::ResolverTimeout504,
-                        _ => QueryError::Unknown,
+                            }
+                        }
+                        // If the status is retryable, the request will be retried on the next
+                        // server if one is available.
+                        status if self.retryable_statuses.contains(&status) => {
+                            status_error(status, res.headers())
+                        }
+                        status => {
+                            return Err(RequestError::Abort(status_error(status, res.headers())))
+                        }
                     }
                 }
-                Err(_) => QueryError::Connection(format!(
+                None => QueryError::Connection(format!(
                     "connection timeout after {:?}",
-                    server.timeout()
+                    self.server_timeout(server)
                 )),
             };
-            error!("request error on URL {}: {}", url, error);
+        Err(RequestError::Retry(error))
+    }
+
+    // Parses the JSON body returned by the server according to the parsing mode.
+    fn parse_response(&self, body: &[u8]) -> Result<DnsResponse, QueryError> {
+        if self.lenient {
+            lenient::parse_response(body)
+        } else {
+            serde_json::from_slice::<DnsResponse>(body)
+                .map_err(|e| QueryError::ParseResponse(e.to_string()))
+        }
+    }
+}
+
+// The error of a request to a single server. It indicates whether the next server should
+// be tried.
+enum RequestError {
+    Retry(QueryError),
+    Abort(QueryError),
+}
+
+impl RequestError {
+    // Transforms the error while keeping whether the next server should be tried.
+    fn map<F: FnOnce(QueryError) -> QueryError>(self, f: F) -> RequestError {
+        match self {
+            RequestError::Retry(e) => RequestError::Retry(f(e)),
+            RequestError::Abort(e) => RequestError::Abort(f(e)),
         }
-        Err(error)
+    }
+}
+
+// Name has to be puny encoded.
+fn encode_name(name: &str, config: &IdnaConfig) -> Result<String, QueryError> {
+    // A fully qualified name is the same query as the name without its trailing dot.
+    let name = name.strip_suffix('.').unwrap_or(name);
+    if name.ends_with('.') {
+        return Err(QueryError::InvalidName(format!(
+            "{}.: name ends with an empty label",
+            name
+        )));
+    }
+    let name = idna::Config::default()
+        .transitional_processing(config.transitional)
+        .use_std3_ascii_rules(config.std3_rules)
+        .check_hyphens(config.check_hyphens)
+        .to_ascii(name)
+        .map_err(|e| QueryError::InvalidName(format!("{:?}", e)))?;
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(QueryError::InvalidName(format!(
+            "name is longer than {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    Ok(name)
+}
+
+// Checks that the name is a complete reverse name of an IPv4 or IPv6 address and returns it
+// in lowercase without a trailing dot.
+fn validate_arpa(name: &str) -> Result<String, QueryError> {
+    let name = name.trim();
+    let name = name.strip_suffix('.').unwrap_or(name).to_ascii_lowercase();
+    let invalid = |reason: &str| QueryError::InvalidName(format!("{}: {}", name, reason));
+    if let Some(octets) = name.strip_suffix(".in-addr.arpa") {
+        let octets = octets.split('.').collect::<Vec<_>>();
+        if octets.len() != 4 {
+            return Err(invalid("expected 4 octets before in-addr.arpa"));
+        }
+        if octets
+            .iter()
+            .any(|o| o.parse::<u8>().is_err() || (o.len() > 1 && o.starts_with('0')))
+        {
+            return Err(invalid("octets must be decimal numbers from 0 to 255"));
+        }
+    } else if let Some(nibbles) = name.strip_suffix(".ip6.arpa") {
+        let nibbles = nibbles.split('.').collect::<Vec<_>>();
+        if nibbles.len() != 32 {
+            return Err(invalid("expected 32 nibbles before ip6.arpa"));
+        }
+        if nibbles
+            .iter()
+            .any(|n| n.len() != 1 || !n.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            return Err(invalid("nibbles must be single hexadecimal digits"));
+        }
+    } else {
+        return Err(invalid(
+            "expected a name ending in in-addr.arpa or ip6.arpa",
+        ));
+    }
+    Ok(name)
+}
+
+// Returns the reverse name of an IP address, under `in-addr.arpa` for IPv4 and `ip6.arpa`
+// for IPv6.
+fn reverse_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, d] = ip.octets();
+            format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a)
+        }
+        IpAddr::V6(ip) => {
+            let mut name = String::with_capacity(72);
+            for byte in ip.octets().iter().rev() {
+                name.push_str(&format!("{:x}.{:x}.", byte & 0xf, byte >> 4));
+            }
+            name.push_str("ip6.arpa");
+            name
+        }
+    }
+}
+
+// Returns the location of a redirect response if it is an absolute `https` URL.
+fn redirect_location(res: &Response<Body>) -> Option<Uri> {
+    if !matches!(res.status().as_u16(), 301 | 302 | 307 | 308) {
+        return None;
+    }
+    let location = res.headers().get(LOCATION)?.to_str().ok()?;
+    let location = location.trim().parse::<Uri>().ok()?;
+    match (location.scheme_str(), location.host()) {
+        (Some("https"), Some(_)) => Some(location),
+        _ => None,
+    }
+}
+
+// Converts an HTTP error status returned by a server to its error.
+fn status_error(status: u16, headers: &HeaderMap) -> QueryError {
+    match status {
+        400 => QueryError::BadRequest400,
+        413 => QueryError::PayloadTooLarge413,
+        414 => QueryError::UriTooLong414,
+        415 => QueryError::UnsupportedMediaType415,
+        429 => QueryError::TooManyRequests429,
+        500 => QueryError::InternalServerError500,
+        501 => QueryError::NotImplemented501,
+        502 => QueryError::BadGateway502,
+        // Only the delay in seconds form of `Retry-After` is supported, not the date form.
+        503 => QueryError::ServiceUnavailable503(
+            headers
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse().ok())
+                .map(Duration::from_secs),
+        ),
+        504 => QueryError::ResolverTimeout504,
+        _ => QueryError::Unknown,
+    }
+}
+
+// Returns the separator between the server URI and the query parameters. The URI may
+// already contain a path and its own query parameters such as an access token.
+fn query_separator(uri: &str) -> &'static str {
+    match uri.find('?') {
+        None => "?",
+        Some(i) if i == uri.len() - 1 || uri.ends_with('&') => "",
+        Some(_) => "&",
+    }
+}
+
+// Converts the server response into the answers of the given rtype or the error returned by
+// the server. Answers that are not of the given rtype are filtered out with the exception
+// of `ANY`.
+fn process_response(res: DnsResponse, rtype: &Rtype) -> Result<Vec<DnsAnswer>, DnsError> {
+    match num::FromPrimitive::from_u32(res.Status) {
+        Some(RCode::NoError) => Ok(filter_answers(res.Answer, rtype)),
+        Some(code) => Err(DnsError::Status(code)),
+        None => Err(DnsError::Status(RCode::Unknown)),
+    }
+}
+
+// Gets only the record types requested. There is only exception and that is the ANY record
+// which has a value of 0.
+fn filter_answers(answers: Option<Vec<DnsAnswer>>, rtype: &Rtype) -> Vec<DnsAnswer> {
+    answers
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|a| is_answer(a, rtype))
+        .collect()
+}
+
+// Whether the response has answers of the given rtype, or any answer for `ANY`.
+fn has_answers(res: &DnsResponse, rtype: &Rtype) -> bool {
+    res.Answer
+        .as_ref()
+        .is_some_and(|answers| answers.iter().any(|a| is_answer(a, rtype)))
+}
+
+// Whether the answer is of the given rtype. For `ANY`, all answers are except the `OPT`
+// pseudo-records of EDNS that some servers include, which are not records of the name.
+fn is_answer(answer: &DnsAnswer, rtype: &Rtype) -> bool {
+    match rtype.0 {
+        0 => answer.r#type != RecordType::OPT as u32,
+        rtype => answer.r#type == rtype,
+    }
+}
+
+// Resolves `A` and `AAAA` records for a name that is already encoded with the system
+// resolver. It returns `None` for other record types or if the lookup fails.
+#[cfg(feature = "system-fallback")]
+async fn system_lookup(name: &str, rtype: &Rtype) -> Option<DnsResponse> {
+    let ipv6 = match rtype.0 {
+        1 => false,
+        28 => true,
+        _ => return None,
+    };
+    let mut ips = tokio::net::lookup_host((name, 0))
+        .await
+        .ok()?
+        .map(|addr| addr.ip())
+        .filter(|ip| ip.is_ipv6() == ipv6)
+        .collect::<Vec<_>>();
+    ips.sort_unstable();
+    ips.dedup();
+    let answers = ips
+        .iter()
+        .map(|ip| DnsAnswer {
+            name: format!("{}.", name),
+            r#type: rtype.0,
+            TTL: 0,
+            data: ip.to_string(),
+        })
+        .collect();
+    Some(DnsResponse {
+        Status: RCode::NoError as u32,
+        TC: false,
+        RD: true,
+        RA: true,
+        AD: false,
+        CD: false,
+        Answer: Some(answers),
+        Authority: None,
+        Comment: None,
+    })
+}
+
+// Builds the reply to a query from the response regardless of its status.
+fn build_reply(res: DnsResponse, rtype: &Rtype) -> DnsReply {
+    DnsReply {
+        status: res.Status,
+        answers: filter_answers(res.Answer, rtype),
+        authority: res.Authority.unwrap_or_default(),
+        truncated: res.TC,
+        recursion_desired: res.RD,
+        recursion_available: res.RA,
+        authenticated_data: res.AD,
+        checking_disabled: res.CD,
+        comment: res.Comment,
+    }
+}
+
+// Decompresses the response body according to its `Content-Encoding`. Chunked transfer
+// encoding is already handled by `hyper`. Without the `compression` feature, encoded bodies
+// are reported as a read error rather than failing later with an obscure parse error.
+fn decode_body<'a>(encoding: Option<&str>, body: &'a [u8]) -> Result<Cow<'a, [u8]>, QueryError> {
+    match encoding {
+        None | Some("") | Some("identity") => Ok(Cow::Borrowed(body)),
+        #[cfg(feature = "compression")]
+        Some(encoding @ "gzip") | Some(encoding @ "x-gzip") => {
+            read_decoder(encoding, flate2::read::GzDecoder::new(body))
+        }
+        #[cfg(feature = "compression")]
+        Some(encoding @ "deflate") => read_decoder(encoding, flate2::read::ZlibDecoder::new(body)),
+        #[cfg(feature = "compression")]
+        Some(encoding @ "br") => read_decoder(encoding, brotli::Decompressor::new(body, 4096)),
+        Some(encoding) => Err(QueryError::ReadResponse(format!(
+            "unsupported content encoding: {}",
+            encoding
+        ))),
+    }
+}
+
+#[cfg(feature = "compression")]
+fn read_decoder<'a, R: std::io::Read>(
+    encoding: &str,
+    mut decoder: R,
+) -> Result<Cow<'a, [u8]>, QueryError> {
+    let mut decoded = Vec::new();
+    match decoder.read_to_end(&mut decoded) {
+        Ok(_) => Ok(Cow::Owned(decoded)),
+        Err(e) => Err(QueryError::ReadResponse(format!(
+            "error decoding {} response: {}",
+            encoding, e
+        ))),
     }
 }
 
@@ -154,12 +2260,12 @@ macro_rules! rtypes {
             impl<C: DnsClient, S: DnsHttpsServer> Dns<C, S> {
                 $(
                     $(#[$docs])*
-                    pub async fn [<resolve_ $konst>](&self, name: &str) -> Result<Vec<DnsAnswer>, DnsError> {
-                        self.request_and_process(name, &[<RTYPE_ $konst>]).await
+                    pub async fn [<resolve_ $konst>](&self, name: impl AsRef<str>) -> Result<Vec<DnsAnswer>, DnsError> {
+                        self.request_and_process(name.as_ref(), &[<RTYPE_ $konst>], None).await
                     }
                 )+
 
-                pub async fn resolve_str_type(&self, name: &str, rtype: &str) -> Result<Vec<DnsAnswer>, DnsError> {
+                pub async fn resolve_str_type(&self, name: impl AsRef<str>, rtype: &str) -> Result<Vec<DnsAnswer>, DnsError> {
                     match rtype.to_ascii_lowercase().as_ref() {
                         $(
                         stringify!($konst) => self.[<resolve_ $konst>](name).await,
@@ -168,13 +2274,17 @@ macro_rules! rtypes {
                     }
                 }
 
-                /// Converts the given record type to a string representation.
+                /// Converts the given record type to a string representation. Types that cannot
+                /// be queried such as `OPT` are named using [RecordType].
                 pub fn rtype_to_name(&self, rtype: u32) -> String {
                     let name = match rtype {
                         $(
                         $num => stringify!($konst),
                         )+
-                        _ => "unknown",
+                        _ => match RecordType::from_u32(rtype) {
+                            Some(rtype) => rtype.name(),
+                            None => "unknown",
+                        },
                     };
                     name.to_ascii_uppercase()
                 }
@@ -183,6 +2293,16 @@ macro_rules! rtypes {
             #[allow(non_upper_case_globals)]
             const [<RTYPE_ $konst>]: Rtype = Rtype($num, stringify!($konst));
         )+
+
+        // Returns the queryable record type with the given name.
+        fn rtype_from_name(rtype: &str) -> Option<&'static Rtype> {
+            match rtype.to_ascii_lowercase().as_ref() {
+                $(
+                stringify!($konst) => Some(&[<RTYPE_ $konst>]),
+                )+
+                _ => None,
+            }
+        }
         }
     }
 }
@@ -194,7 +2314,9 @@ rtypes! {
     (a, 1);
     /// Queries an IP6 Address for the given name.
     (aaaa, 28);
-    /// Queries all record types for a given name.
+    /// Queries all record types for a given name. On servers that do not support `ANY`
+    /// queries, as given by [DnsHttpsServer::supports_any], the common record types are
+    /// queried instead.
     (any, 0);
     /// Queries a certification authority restriction record for the given name.
     (caa, 257);
@@ -1,6 +1,6 @@
 //! Errors returned by DoH servers.
 use crate::status::RCode;
-use std::{error::Error, fmt};
+use std::{error::Error, fmt, time::Duration};
 
 /// Errors returned before or after making a DNS request over HTTPS.
 #[derive(Debug)]
@@ -16,6 +16,15 @@ pub enum DnsError {
     InvalidRecordType,
     /// An error when trying to setup an empty list of servers to query.
     NoServers,
+    /// An error returned when a query did not complete before its deadline, such as the
+    /// deadline of [Dns::resolve_many](crate::Dns::resolve_many).
+    Timeout,
+    /// An error returned for a name that is set to not be sent to the servers with
+    /// [Dns::with_local_names](crate::Dns::with_local_names).
+    LocalName(String),
+    /// An error returned for a successful query without answers when
+    /// [Dns::with_empty_as_error](crate::Dns::with_empty_as_error) is enabled.
+    NoAnswers,
 }
 
 impl fmt::Display for DnsError {
@@ -25,6 +34,9 @@ impl fmt::Display for DnsError {
             DnsError::Status(ref e) => write!(f, "DNS response error: {}", e),
             DnsError::InvalidRecordType => write!(f, "Invalid record type"),
             DnsError::NoServers => write!(f, "no servers given to resolve query"),
+            DnsError::Timeout => write!(f, "query timed out"),
+            DnsError::LocalName(ref name) => write!(f, "local name not sent to servers: {}", name),
+            DnsError::NoAnswers => write!(f, "no answers found"),
         }
     }
 }
@@ -38,12 +50,15 @@ impl Error for DnsError {
 /// Errors returned in the process of generating requests and reading responsed from DoH
 /// servers. Google's HTTP response codes can be seen at <https://developers.google.com/speed/public-dns/docs/doh>
 /// and Cloudflare's at <https://developers.cloudflare.com/1.1.1.1/dns-over-https/request-structure>.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum QueryError {
     /// This error occurs if the name to be resolved cannot be encoded.
     InvalidName(String),
     /// This error occurs if there is a problem building the query URL.
     InvalidEndpoint(String),
+    /// This error occurs if the query URL is longer than servers accept, in which case the
+    /// request is not sent. It holds the length of the URL.
+    UrlTooLong(usize),
     /// This error occurs if there is a problem connecting to the server.
     Connection(String),
     /// This error occurs if there is a problem reading a response from the server.
@@ -77,9 +92,42 @@ pub enum QueryError {
     /// *HTTP Error: 502 Bad Gateway.*
     /// The DoH service could not contact Google Public DNS resolvers.
     BadGateway502,
+    /// *HTTP Error: 503 Service Unavailable.*
+    /// The service is temporarily unavailable, such as during maintenance. It holds the delay
+    /// of the `Retry-After` header if the server sent one in seconds.
+    ServiceUnavailable503(Option<Duration>),
     /// *HTTP Error: 504.*
     /// Resolver timeout while waiting for the query response.
     ResolverTimeout504,
+    /// An error that occurred while querying the given URL. Every error from a request to a
+    /// server is returned with the URL that was queried.
+    AtUrl(String, Box<QueryError>),
+}
+
+impl QueryError {
+    /// Returns the URL that was queried when the error occurred, if any.
+    pub fn url(&self) -> Option<&str> {
+        match *self {
+            QueryError::AtUrl(ref url, _) => Some(url),
+            _ => None,
+        }
+    }
+
+    /// Returns how long the server asked to wait before retrying, if it did.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match *self.root() {
+            QueryError::ServiceUnavailable503(retry_after) => retry_after,
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying error without the URL that was queried.
+    pub fn root(&self) -> &QueryError {
+        match *self {
+            QueryError::AtUrl(_, ref e) => e.root(),
+            ref e => e,
+        }
+    }
 }
 
 impl fmt::Display for QueryError {
@@ -87,6 +135,7 @@ impl fmt::Display for QueryError {
         match *self {
             QueryError::InvalidName(ref e) => write!(f, "invalid server name given: {}", e),
             QueryError::InvalidEndpoint(ref e) => write!(f, "invalid endpoint: {}", e),
+            QueryError::UrlTooLong(len) => write!(f, "query URL too long: {} bytes", len),
             QueryError::Connection(ref e) => write!(f, "connection error: {}", e),
             QueryError::ReadResponse(ref e) => write!(f, "error reading response: {}", e),
             QueryError::ParseResponse(ref e) => write!(f, "error parsing response: {}", e),
@@ -123,15 +172,48 @@ impl fmt::Display for QueryError {
                 f,
                 "The DoH service could not contact Google Public DNS resolvers"
             ),
+            QueryError::ServiceUnavailable503(_) => write!(
+                f,
+                "The service is temporarily unavailable"
+            ),
             QueryError::ResolverTimeout504 => write!(
                 f,
                 "Resolver timeout while waiting for the query response"
             ),
+            QueryError::AtUrl(ref url, ref e) => write!(f, "{} (URL: {})", e, url),
         }
     }
 }
 
 impl Error for QueryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            QueryError::AtUrl(_, ref e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned when the data of an answer cannot be parsed according to its type.
+#[derive(Debug)]
+pub struct ParseError {
+    /// The type of the answer.
+    pub rtype: u32,
+    /// The data that could not be parsed.
+    pub data: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid data for record type {}: {}",
+            self.rtype, self.data
+        )
+    }
+}
+
+impl Error for ParseError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         None
     }
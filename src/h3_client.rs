@@ -0,0 +1,150 @@
+//! HTTP/3 client to query DoH servers over QUIC.
+use crate::client::DnsClient;
+use async_trait::async_trait;
+use bytes::{Buf, Bytes};
+use futures_util::future::poll_fn;
+use futures_util::stream;
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+// The port of a server URI without one.
+const DEFAULT_PORT: u16 = 443;
+
+type SendRequest = h3::client::SendRequest<h3_quinn::OpenStreams, Bytes>;
+
+/// DNS client over HTTP/3. It keeps a QUIC connection to each server and sends every request
+/// to that server over it, so only the first request pays for the connection setup. Server
+/// certificates are validated against the Mozilla root certificates.
+///
+/// It requires the `h3` feature and must be used within a `tokio` runtime.
+pub struct H3DnsClient {
+    tls_config: Arc<rustls::ClientConfig>,
+    // Open connections keyed by the authority of the server URI.
+    connections: Mutex<HashMap<String, SendRequest>>,
+}
+
+impl Default for H3DnsClient {
+    fn default() -> H3DnsClient {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        // HTTP/3 requires TLS 1.3.
+        let mut tls_config = rustls::ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .expect("TLS 1.3 support")
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+        tls_config.alpn_protocols = vec![b"h3".to_vec()];
+        H3DnsClient {
+            tls_config: Arc::new(tls_config),
+            connections: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl H3DnsClient {
+    // Returns the open connection to the server of the URI or opens a new one.
+    async fn connection(&self, uri: &Uri) -> io::Result<SendRequest> {
+        let authority = match uri.authority() {
+            Some(authority) => authority.as_str().to_ascii_lowercase(),
+            None => return Err(io_error("URI has no host")),
+        };
+        if let Some(send_request) = self.connections.lock().unwrap().get(&authority) {
+            return Ok(send_request.clone());
+        }
+        let host = uri
+            .host()
+            .map(|h| h.trim_start_matches('[').trim_end_matches(']'))
+            .unwrap_or_default();
+        let addr = tokio::net::lookup_host((host, uri.port_u16().unwrap_or(DEFAULT_PORT)))
+            .await?
+            .next()
+            .ok_or_else(|| io_error(format!("no address found for {}", host)))?;
+        let bind: SocketAddr = match addr {
+            SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+        };
+        let mut endpoint = quinn::Endpoint::client(bind)?;
+        let crypto = quinn::crypto::rustls::QuicClientConfig::try_from(self.tls_config.clone())
+            .map_err(io_error)?;
+        endpoint.set_default_client_config(quinn::ClientConfig::new(Arc::new(crypto)));
+        let connection = endpoint
+            .connect(addr, host)
+            .map_err(io_error)?
+            .await
+            .map_err(io_error)?;
+        let (mut driver, send_request) = h3::client::new(h3_quinn::Connection::new(connection))
+            .await
+            .map_err(io_error)?;
+        // The driver runs the connection until it is closed by either side.
+        tokio::spawn(async move {
+            let _ = poll_fn(|cx| driver.poll_close(cx)).await;
+        });
+        self.connections
+            .lock()
+            .unwrap()
+            .insert(authority, send_request.clone());
+        Ok(send_request)
+    }
+
+    // Sends a GET request over the connection to the server and reads the whole response.
+    async fn request(&self, uri: &Uri) -> io::Result<Response<Body>> {
+        let mut send_request = self.connection(uri).await?;
+        let req = http::Request::builder()
+            .method("GET")
+            .uri(uri.to_string())
+            .header("Accept", "application/dns-json")
+            .body(())
+            .map_err(io_error)?;
+        let mut stream = send_request.send_request(req).await.map_err(io_error)?;
+        stream.finish().await.map_err(io_error)?;
+        let res = stream.recv_response().await.map_err(io_error)?;
+        let mut body = Vec::new();
+        while let Some(mut chunk) = stream.recv_data().await.map_err(io_error)? {
+            while chunk.has_remaining() {
+                let bytes = chunk.chunk();
+                body.extend_from_slice(bytes);
+                let len = bytes.len();
+                chunk.advance(len);
+            }
+        }
+        // The response is converted to the `http` version used by `hyper`.
+        let mut builder = Response::builder().status(res.status().as_u16());
+        for (name, value) in res.headers() {
+            builder = builder.header(name.as_str(), value.as_bytes());
+        }
+        builder.body(Body::from(body)).map_err(io_error)
+    }
+}
+
+#[async_trait]
+impl DnsClient for H3DnsClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        match self.request(&uri).await {
+            Ok(res) => Ok(res),
+            Err(e) => {
+                // A broken connection is opened again on the next request.
+                if let Some(authority) = uri.authority() {
+                    self.connections
+                        .lock()
+                        .unwrap()
+                        .remove(&authority.as_str().to_ascii_lowercase());
+                }
+                // A `hyper::Error` cannot be created outside of `hyper`, so the error is
+                // returned by the body instead, which fails to be read and is retried on the
+                // next server like a connection error.
+                let body = Body::wrap_stream(stream::once(async move { Err::<Bytes, _>(e) }));
+                Ok(Response::new(body))
+            }
+        }
+    }
+}
+
+fn io_error<E: Display>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("HTTP/3: {}", e))
+}
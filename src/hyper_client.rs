@@ -0,0 +1,373 @@
+//! Hyper-based HTTPS client to query DoH servers.
+use crate::client::DnsClient;
+use crate::error::{DnsError, QueryError};
+use async_trait::async_trait;
+
+use futures_util::future::{poll_fn, BoxFuture};
+use hyper::{
+    client::{
+        connect::dns::{GaiResolver, Name},
+        HttpConnector,
+    },
+    header::HeaderMap,
+    Body, Client, Request, Response, Result as HyperResult, Uri,
+};
+use hyper_tls::{HttpsConnector, MaybeHttpsStream};
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower_service::Service;
+
+/// Hyper-based DNS client over SSL and with a static resolver to resolve DNS server names
+/// such as `dns.google` since Google does not accept request over `8.8.8.8` like Cloudflare
+/// does over `1.1.1.1`.
+pub struct HyperDnsClient {
+    client: Client<HttpsConnector<HttpConnector<StaticResolver>>>,
+    resolver: StaticResolver,
+    config: ClientConfig,
+    // Hostnames to use in place of the IP addresses of server URIs.
+    hostnames: HashMap<IpAddr, String>,
+}
+
+/// The TLS details of a connection to a DoH server, as returned by
+/// [HyperDnsClient::inspect_tls]. The protocol version and cipher suite are negotiated by
+/// the platform TLS library, which does not expose them, so they are not included.
+#[derive(Debug, Clone)]
+pub struct TlsInfo {
+    /// The DER encoded certificate presented by the server.
+    pub peer_certificate: Option<Vec<u8>>,
+    /// The hash of the server certificate computed with the hash algorithm of its signature,
+    /// as defined for the `tls-server-end-point` channel binding. For the common SHA-256
+    /// signed certificates this is their SHA-256 fingerprint.
+    pub certificate_hash: Option<Vec<u8>>,
+}
+
+/// The TLS protocol versions that can be required of connections to DoH servers with
+/// [HyperDnsClient::with_min_tls_version].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TlsVersion {
+    /// TLS 1.2.
+    Tls12,
+    /// TLS 1.3. It is not supported on macOS and iOS.
+    Tls13,
+}
+
+// The settings the client is built with.
+#[derive(Clone)]
+struct ClientConfig {
+    local_address: Option<IpAddr>,
+    min_tls_version: Option<TlsVersion>,
+    accept_invalid_hostnames: bool,
+    root_certs: Vec<native_tls::Certificate>,
+    identity: Option<native_tls::Identity>,
+}
+
+impl HyperDnsClient {
+    /// Creates a client that connects to the given IP addresses while presenting the paired
+    /// hostname for SNI and certificate validation, e.g. `(8.8.8.8, "dns.google")`. Server
+    /// URIs may use either the IP address or the hostname. The hostnames are never resolved
+    /// with the system resolver and certificates are validated against them.
+    pub fn with_bootstrap_ips(hosts: &[(IpAddr, &str)]) -> HyperDnsClient {
+        let resolver = StaticResolver {
+            hosts: Arc::new(
+                hosts
+                    .iter()
+                    .map(|(ip, hostname)| (hostname.to_ascii_lowercase(), *ip))
+                    .collect(),
+            ),
+            gai: GaiResolver::new(),
+            cache_ttl: None,
+            cache: Arc::default(),
+        };
+        let config = ClientConfig {
+            local_address: None,
+            min_tls_version: None,
+            accept_invalid_hostnames: false,
+            root_certs: Vec::new(),
+            identity: None,
+        };
+        HyperDnsClient {
+            client: build_client(resolver.clone(), &config).unwrap(),
+            resolver,
+            config,
+            hostnames: hosts
+                .iter()
+                .map(|(ip, hostname)| (*ip, hostname.to_ascii_lowercase()))
+                .collect(),
+        }
+    }
+
+    /// Adds a PEM encoded certificate to the trusted root certificates. This allows using
+    /// DoH servers with certificates issued by a private certificate authority.
+    pub fn with_root_cert(mut self, pem: &[u8]) -> Result<Self, native_tls::Error> {
+        self.config
+            .root_certs
+            .push(native_tls::Certificate::from_pem(pem)?);
+        self.rebuild()
+    }
+
+    /// Sets the client certificate chain and its PKCS #8 private key, both PEM encoded, that
+    /// are presented to DoH servers requiring mutual TLS authentication.
+    pub fn with_client_identity(
+        mut self,
+        pem: &[u8],
+        key: &[u8],
+    ) -> Result<Self, native_tls::Error> {
+        self.config.identity = Some(native_tls::Identity::from_pkcs8(pem, key)?);
+        self.rebuild()
+    }
+
+    /// Sets the local address that connections to DoH servers originate from. This selects
+    /// the outgoing interface on hosts with several of them.
+    pub fn with_local_address(mut self, address: IpAddr) -> Self {
+        self.config.local_address = Some(address);
+        // The TLS settings were already validated when the client was built.
+        self.rebuild().expect("client settings")
+    }
+
+    /// Caches the addresses of DoH server hostnames resolved with the system resolver for the
+    /// given duration, so that new connections to a server do not resolve its hostname again.
+    /// The system resolver does not return the TTL of the addresses so the duration is used
+    /// for all of them. This is separate from the cache of answers of
+    /// [Dns::with_cache](crate::Dns::with_cache). By default, hostnames are resolved for every
+    /// new connection.
+    pub fn with_host_cache(mut self, ttl: Duration) -> Self {
+        self.resolver.cache_ttl = Some(ttl);
+        // The TLS settings were already validated when the client was built.
+        self.rebuild().expect("client settings")
+    }
+
+    /// Sets the lowest TLS protocol version accepted for connections to DoH servers. By
+    /// default, the minimum version of the platform TLS library is used, which may allow
+    /// versions older than TLS 1.2 on some platforms.
+    pub fn with_min_tls_version(mut self, version: TlsVersion) -> Result<Self, native_tls::Error> {
+        self.config.min_tls_version = Some(version);
+        self.rebuild()
+    }
+
+    /// Opens a connection to the server of the given URI with the settings of this client and
+    /// returns the TLS details of that connection. The connection is closed afterwards and no
+    /// query is made.
+    pub async fn inspect_tls(&self, server_uri: &str) -> Result<TlsInfo, DnsError> {
+        let uri = server_uri
+            .parse::<Uri>()
+            .map_err(|e| DnsError::Query(QueryError::InvalidEndpoint(e.to_string())))?;
+        let mut connector = build_connector(self.resolver.clone(), &self.config)
+            .map_err(|e| DnsError::Query(QueryError::Connection(e.to_string())))?;
+        poll_fn(|cx| connector.poll_ready(cx))
+            .await
+            .map_err(|e| DnsError::Query(QueryError::Connection(e.to_string())))?;
+        let stream = connector
+            .call(self.rewrite_uri(uri))
+            .await
+            .map_err(|e| DnsError::Query(QueryError::Connection(e.to_string())))?;
+        let tls = match stream {
+            MaybeHttpsStream::Https(ref tls) => tls.get_ref(),
+            MaybeHttpsStream::Http(_) => {
+                return Err(DnsError::Query(QueryError::Connection(
+                    "connection is not encrypted".to_string(),
+                )))
+            }
+        };
+        let tls_error =
+            |e: native_tls::Error| DnsError::Query(QueryError::Connection(e.to_string()));
+        Ok(TlsInfo {
+            peer_certificate: match tls.peer_certificate().map_err(tls_error)? {
+                Some(cert) => Some(cert.to_der().map_err(tls_error)?),
+                None => None,
+            },
+            certificate_hash: tls.tls_server_end_point().map_err(tls_error)?,
+        })
+    }
+
+    // Builds a new client with the current settings.
+    fn rebuild(mut self) -> Result<Self, native_tls::Error> {
+        self.client = build_client(self.resolver.clone(), &self.config)?;
+        Ok(self)
+    }
+
+    // Replaces the host of the URI with its paired hostname if it is a bootstrap IP address.
+    fn rewrite_uri(&self, uri: Uri) -> Uri {
+        let hostname = match uri
+            .host()
+            .map(|h| h.trim_start_matches('[').trim_end_matches(']'))
+            .and_then(|h| h.parse::<IpAddr>().ok())
+            .and_then(|ip| self.hostnames.get(&ip))
+        {
+            Some(hostname) => hostname,
+            None => return uri,
+        };
+        let authority = match uri.port_u16() {
+            Some(port) => format!("{}:{}", hostname, port),
+            None => hostname.clone(),
+        };
+        let mut parts = uri.clone().into_parts();
+        match authority.parse() {
+            Ok(authority) => parts.authority = Some(authority),
+            Err(_) => return uri,
+        }
+        Uri::from_parts(parts).unwrap_or(uri)
+    }
+}
+
+impl Default for HyperDnsClient {
+    fn default() -> HyperDnsClient {
+        let resolver = StaticResolver {
+            hosts: Arc::new(HashMap::new()),
+            gai: GaiResolver::new(),
+            cache_ttl: None,
+            cache: Arc::default(),
+        };
+        let config = ClientConfig {
+            local_address: None,
+            min_tls_version: None,
+            accept_invalid_hostnames: true,
+            root_certs: Vec::new(),
+            identity: None,
+        };
+        HyperDnsClient {
+            client: build_client(resolver.clone(), &config).unwrap(),
+            resolver,
+            config,
+            hostnames: HashMap::new(),
+        }
+    }
+}
+
+// Builds an HTTPS only client using the given resolver.
+fn build_client(
+    resolver: StaticResolver,
+    config: &ClientConfig,
+) -> Result<Client<HttpsConnector<HttpConnector<StaticResolver>>>, native_tls::Error> {
+    Ok(Client::builder().build(build_connector(resolver, config)?))
+}
+
+// Builds an HTTPS only connector using the given resolver.
+fn build_connector(
+    resolver: StaticResolver,
+    config: &ClientConfig,
+) -> Result<HttpsConnector<HttpConnector<StaticResolver>>, native_tls::Error> {
+    let mut http_connector = HttpConnector::new_with_resolver(resolver);
+    http_connector.enforce_http(false);
+    http_connector.set_local_address(config.local_address);
+    let mut tls_connector = native_tls::TlsConnector::builder();
+    tls_connector.danger_accept_invalid_hostnames(config.accept_invalid_hostnames);
+    if let Some(version) = config.min_tls_version {
+        tls_connector.min_protocol_version(Some(match version {
+            TlsVersion::Tls12 => native_tls::Protocol::Tlsv12,
+            TlsVersion::Tls13 => native_tls::Protocol::Tlsv13,
+        }));
+    }
+    for cert in config.root_certs.iter() {
+        tls_connector.add_root_certificate(cert.clone());
+    }
+    if let Some(ref identity) = config.identity {
+        tls_connector.identity(identity.clone());
+    }
+    let mut connector = HttpsConnector::from((http_connector, tls_connector.build()?.into()));
+    connector.https_only(true);
+    Ok(connector)
+}
+
+#[async_trait]
+impl DnsClient for HyperDnsClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        self.get_with_headers(uri, &HeaderMap::new()).await
+    }
+
+    async fn post(&self, uri: Uri, body: String) -> HyperResult<Response<Body>> {
+        self.post_with_headers(uri, body, &HeaderMap::new()).await
+    }
+
+    async fn get_with_headers(&self, uri: Uri, headers: &HeaderMap) -> HyperResult<Response<Body>> {
+        // The reason to build a request manually is to set the Accept header required by
+        // DNS servers.
+        let mut req = Request::builder()
+            .method("GET")
+            .uri(self.rewrite_uri(uri))
+            .header("Accept", "application/dns-json")
+            .body(Body::default())
+            .expect("request builder");
+        req.headers_mut().extend(headers.clone());
+        self.client.request(req).await
+    }
+
+    async fn post_with_headers(
+        &self,
+        uri: Uri,
+        body: String,
+        headers: &HeaderMap,
+    ) -> HyperResult<Response<Body>> {
+        let mut req = Request::builder()
+            .method("POST")
+            .uri(self.rewrite_uri(uri))
+            .header("Accept", "application/dns-json")
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .expect("request builder");
+        req.headers_mut().extend(headers.clone());
+        self.client.request(req).await
+    }
+}
+
+// Addresses resolved by the system resolver by hostname along with when they expire.
+type HostCache = HashMap<String, (Instant, Vec<SocketAddr>)>;
+
+/// Resolves hostnames with a static table of addresses and falls back to the system
+/// resolver for any other hostname.
+#[derive(Clone)]
+pub struct StaticResolver {
+    hosts: Arc<HashMap<String, IpAddr>>,
+    gai: GaiResolver,
+    // How long the addresses from the system resolver are cached, if they are.
+    cache_ttl: Option<Duration>,
+    // The addresses cached for `cache_ttl`.
+    cache: Arc<Mutex<HostCache>>,
+}
+
+impl Service<Name> for StaticResolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.gai.poll_ready(cx)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        // The port is set by the connector from the URI.
+        if let Some(ip) = self.hosts.get(&name.as_str().to_ascii_lowercase()) {
+            let addrs = vec![SocketAddr::new(*ip, 0)];
+            return Box::pin(async move { Ok(addrs.into_iter()) });
+        }
+        let ttl = match self.cache_ttl {
+            Some(ttl) => ttl,
+            None => {
+                let resolving = self.gai.call(name);
+                return Box::pin(async move {
+                    Ok(resolving.await?.collect::<Vec<SocketAddr>>().into_iter())
+                });
+            }
+        };
+        let hostname = name.as_str().to_ascii_lowercase();
+        if let Some((expiry, addrs)) = self.cache.lock().unwrap().get(&hostname) {
+            if *expiry > Instant::now() {
+                let addrs = addrs.clone();
+                return Box::pin(async move { Ok(addrs.into_iter()) });
+            }
+        }
+        let resolving = self.gai.call(name);
+        let cache = self.cache.clone();
+        Box::pin(async move {
+            let addrs = resolving.await?.collect::<Vec<SocketAddr>>();
+            cache
+                .lock()
+                .unwrap()
+                .insert(hostname, (Instant::now() + ttl, addrs.clone()));
+            Ok(addrs.into_iter())
+        })
+    }
+}
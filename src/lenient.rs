@@ -0,0 +1,147 @@
+//! Lenient parsing of DoH JSON responses. It accepts common deviations from the documented
+//! schema and reports the offending field when a value cannot be coerced.
+use crate::error::QueryError;
+use crate::{DnsAnswer, DnsResponse};
+use serde_json::{Map, Value};
+
+/// Parses a response body, coercing numbers given as strings, missing or `null` fields and
+/// non-string record data.
+pub(crate) fn parse_response(body: &[u8]) -> Result<DnsResponse, QueryError> {
+    let value = serde_json::from_slice::<Value>(body)
+        .map_err(|e| QueryError::ParseResponse(e.to_string()))?;
+    let object = match value {
+        Value::Object(object) => object,
+        other => return Err(invalid("response", "object", &other)),
+    };
+    let status = match object.get("Status") {
+        Some(value) => to_u32("Status", value)?,
+        None => {
+            return Err(QueryError::ParseResponse(
+                "missing field `Status`".to_string(),
+            ))
+        }
+    };
+    let answer = to_answers(&object, "Answer")?;
+    let authority = to_answers(&object, "Authority")?;
+    let comment = match object.get("Comment") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(s)) => Some(s.clone()),
+        // Some providers return a list of comments.
+        Some(Value::Array(comments)) => {
+            Some(comments.iter().map(to_text).collect::<Vec<_>>().join(" "))
+        }
+        Some(other) => Some(to_text(other)),
+    };
+    Ok(DnsResponse {
+        Status: status,
+        TC: to_flag(&object, "TC")?,
+        RD: to_flag(&object, "RD")?,
+        RA: to_flag(&object, "RA")?,
+        AD: to_flag(&object, "AD")?,
+        CD: to_flag(&object, "CD")?,
+        Answer: answer,
+        Authority: authority,
+        Comment: comment,
+    })
+}
+
+// Reads a header flag given as a boolean, a number or a string. A missing flag is not set.
+fn to_flag(object: &Map<String, Value>, field: &str) -> Result<bool, QueryError> {
+    match object.get(field) {
+        None | Some(Value::Null) => Ok(false),
+        Some(Value::Bool(b)) => Ok(*b),
+        Some(Value::Number(n)) if n.as_u64() == Some(0) => Ok(false),
+        Some(Value::Number(n)) if n.as_u64() == Some(1) => Ok(true),
+        Some(Value::String(s)) if s.eq_ignore_ascii_case("true") || s == "1" => Ok(true),
+        Some(Value::String(s)) if s.eq_ignore_ascii_case("false") || s == "0" => Ok(false),
+        Some(other) => Err(invalid(field, "boolean", other)),
+    }
+}
+
+// Reads a section of records such as `Answer` or `Authority`.
+fn to_answers(
+    object: &Map<String, Value>,
+    section: &str,
+) -> Result<Option<Vec<DnsAnswer>>, QueryError> {
+    match object.get(section) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Array(answers)) => Ok(Some(
+            answers
+                .iter()
+                .enumerate()
+                .map(|(i, a)| to_answer(section, i, a))
+                .collect::<Result<Vec<_>, _>>()?,
+        )),
+        // A single record returned without the enclosing array.
+        Some(Value::Object(answer)) => Ok(Some(vec![answer_from_object(section, 0, answer)?])),
+        Some(other) => Err(invalid(section, "array", other)),
+    }
+}
+
+fn to_answer(section: &str, index: usize, value: &Value) -> Result<DnsAnswer, QueryError> {
+    match value {
+        Value::Object(answer) => answer_from_object(section, index, answer),
+        other => Err(invalid(&format!("{}[{}]", section, index), "object", other)),
+    }
+}
+
+fn answer_from_object(
+    section: &str,
+    index: usize,
+    answer: &Map<String, Value>,
+) -> Result<DnsAnswer, QueryError> {
+    let field = |name: &str| format!("{}[{}].{}", section, index, name);
+    let name = match answer.get("name") {
+        Some(Value::String(name)) => name.clone(),
+        Some(other) => return Err(invalid(&field("name"), "string", other)),
+        None => return Err(missing(&field("name"))),
+    };
+    let r#type = match answer.get("type") {
+        Some(value) => to_u32(&field("type"), value)?,
+        None => return Err(missing(&field("type"))),
+    };
+    let ttl = match answer.get("TTL") {
+        Some(Value::Null) | None => 0,
+        Some(value) => to_u32(&field("TTL"), value)?,
+    };
+    let data = match answer.get("data") {
+        Some(Value::Null) | None => String::new(),
+        Some(value) => to_text(value),
+    };
+    Ok(DnsAnswer {
+        name,
+        r#type,
+        TTL: ttl,
+        data,
+    })
+}
+
+fn to_u32(field: &str, value: &Value) -> Result<u32, QueryError> {
+    let number = match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.trim().parse::<u64>().ok(),
+        _ => None,
+    };
+    match number {
+        Some(n) if n <= u64::from(u32::MAX) => Ok(n as u32),
+        _ => Err(invalid(field, "unsigned 32-bit number", value)),
+    }
+}
+
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn missing(field: &str) -> QueryError {
+    QueryError::ParseResponse(format!("missing field `{}`", field))
+}
+
+fn invalid(field: &str, expected: &str, found: &Value) -> QueryError {
+    QueryError::ParseResponse(format!(
+        "invalid field `{}`: expected {}, found `{}`",
+        field, expected, found
+    ))
+}
@@ -12,7 +12,7 @@
 //! `A` records can be queried using [Dns::resolve_a]. The default resolvers use Google
 //! first with a timeout of 3 seconds and Clouflare second with a timeout of 10 seconds.
 //! *Note: Cloudlare does not support queries for `ANY` records. You can use the Google
-//! resolver for that.
+//! resolver for that. Without it, [Dns::resolve_any] queries the common record types instead.
 //!
 //! # Example
 //! ```
@@ -53,15 +53,75 @@
 //! }
 //! ```
 //!
+//! # Query Class
+//! All queries use the `IN` (Internet) class. The JSON APIs of Google and Cloudflare do not
+//! accept a class parameter, so queries in other classes such as `CH` (e.g.
+//! `version.bind`) cannot be made with this library.
+//!
+//! # Names
+//! Names may be given with or without their trailing dot: `example.com.` and `example.com`
+//! are the same query, which is sent and cached as `example.com`. Only one trailing dot is
+//! removed, so a name such as `example.com..` is rejected as invalid. Internationalized
+//! names are converted to their ASCII form as set with [Dns::with_idna_config].
+//!
+//! # Runtime
+//! Requests are made through a [client::DnsClient] and timeouts are enforced with a
+//! [timer::Timer]. By default, [client::HyperDnsClient] and [timer::TokioTimer] are used
+//! which require a `tokio` runtime. To use another executor, disable the default
+//! `tokio-timer` feature and provide a client and a timer for that executor.
+//!
+//! [client::HyperDnsClient] and its TLS stack are behind the default `hyper-client`
+//! feature. Without it, only the types and the parsing of responses are built and requests
+//! are made with a [client::DnsClient] of your own.
+//!
+//! With the `h3` feature, `client::H3DnsClient` queries the servers over HTTP/3 instead and
+//! can be used in place of [client::HyperDnsClient], e.g. as `Dns<H3DnsClient, _>`.
+//!
+//! # Cancellation
+//! All queries are cancelled by dropping their future. The in-flight request and any pending
+//! retries are dropped with it, so no request is left running in the background and no
+//! state of the [Dns] instance is left partially updated. An external cancellation signal
+//! such as a `tokio_util::sync::CancellationToken` can be combined with a query using
+//! `tokio::select!`:
+//! ```ignore
+//! tokio::select! {
+//!     _ = token.cancelled() => println!("Query cancelled."),
+//!     res = dns.resolve_a("memo.com") => println!("{:?}", res),
+//! }
+//! ```
+//!
 //! # Logging
 //! This library uses the `log` crate to log errors during retries. Please see that create
-//! on methods on display such errors. If no logger is setup, nothing will be logged.
+//! on methods on display such errors. If no logger is setup, nothing will be logged. Errors
+//! can be logged only once a query has failed on several servers with
+//! [Dns::with_log_after_failures].
+//!
+//! With the `tracing` feature, each query runs in a `resolve` span and each request to a
+//! server in a `server_attempt` span, at the debug level. Both record the record type, the
+//! status and the latency in milliseconds, and server attempts also record the server URI.
 #![feature(proc_macro_hygiene)]
 #![feature(stmt_expr_attributes)]
+mod breaker;
+mod cache;
 pub mod client;
 mod dns;
 pub mod error;
+#[cfg(feature = "h3")]
+mod h3_client;
+#[cfg(feature = "hyper-client")]
+mod hyper_client;
+mod lenient;
+pub mod records;
+pub mod resolver;
+pub mod rtype;
+pub mod server;
+mod single_flight;
+pub mod stats;
 pub mod status;
+pub mod strategy;
+pub mod timer;
+#[cfg(feature = "tracing")]
+mod trace;
 #[macro_use]
 extern crate serde_derive;
 extern crate num;
@@ -69,9 +129,11 @@ extern crate num;
 extern crate num_derive;
 use std::time::Duration;
 
+pub use dns::DEFAULT_RETRYABLE_STATUSES;
+
 /// The data associated for requests returned by the DNS over HTTPS servers.
 #[allow(non_snake_case)]
-#[derive(Deserialize, Debug, Serialize, Clone)]
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DnsAnswer {
     /// The name of the record.
     pub name: String,
@@ -84,21 +146,243 @@ pub struct DnsAnswer {
     pub data: String,
 }
 
+/// The response to a query along with its header flags, as returned by
+/// [Dns::resolve_with_flags].
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct DnsReply {
+    /// The status code of the response. To convert it to a [status::RCode] use
+    /// [DnsReply::rcode].
+    pub status: u32,
+    /// The answers of the requested record type.
+    pub answers: Vec<DnsAnswer>,
+    /// The records of the authority section, such as the SOA record of the zone in negative
+    /// responses.
+    pub authority: Vec<DnsAnswer>,
+    /// Whether the response was truncated (`TC`). The answers may then be incomplete and the
+    /// query should be retried with a transport that can carry the full response.
+    pub truncated: bool,
+    /// Whether recursion was desired (`RD`).
+    pub recursion_desired: bool,
+    /// Whether recursion was available (`RA`).
+    pub recursion_available: bool,
+    /// Whether the answers were validated with DNSSEC (`AD`).
+    pub authenticated_data: bool,
+    /// Whether DNSSEC validation was disabled (`CD`).
+    pub checking_disabled: bool,
+    /// A comment from the server, if any.
+    pub comment: Option<String>,
+}
+
+impl DnsReply {
+    /// Returns the status code of the response.
+    pub fn rcode(&self) -> status::RCode {
+        num::FromPrimitive::from_u32(self.status).unwrap_or(status::RCode::Unknown)
+    }
+}
+
+/// The UTS #46 options used to convert internationalized names to their ASCII form, set
+/// with [Dns::with_idna_config]. The default options are those of the URL standard, which
+/// use non-transitional processing so that `faß.de` is queried as `xn--fa-hia.de` rather than
+/// `fass.de`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct IdnaConfig {
+    /// Whether deviation characters such as `ß` are mapped as in IDNA2003, e.g. to `ss`.
+    pub transitional: bool,
+    /// Whether names must only contain letters, digits and hyphens (STD3 rules).
+    pub std3_rules: bool,
+    /// Whether labels starting or ending with a hyphen, or with hyphens in the third and
+    /// fourth positions, are rejected.
+    pub check_hyphens: bool,
+}
+
+/// The header flags sent with queries, set together with [Dns::with_flags]. The flags are
+/// sent as query parameters that are honored by servers that support them, such as `cd` and
+/// `do` by Google. By default, only recursion is desired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DnsFlags {
+    /// Whether recursion is desired (`RD`). It is sent as `rd=0` when disabled.
+    pub recursion_desired: bool,
+    /// Whether DNSSEC validation is disabled (`CD`). It is sent as `cd=1` when enabled.
+    pub checking_disabled: bool,
+    /// Whether DNSSEC records such as RRSIG are requested (`DO`). It is sent as `do=1` when
+    /// enabled.
+    pub dnssec_ok: bool,
+    /// Whether the authenticated data bit is requested (`AD`) as defined in RFC 6840. It is
+    /// sent as `ad=1` when enabled.
+    pub authenticated_data: bool,
+}
+
+impl Default for DnsFlags {
+    fn default() -> DnsFlags {
+        DnsFlags {
+            recursion_desired: true,
+            checking_disabled: false,
+            dnssec_ok: false,
+            authenticated_data: false,
+        }
+    }
+}
+
+/// The answers of two servers to the same query, as returned by [Dns::compare]. Answers are
+/// matched by name, type and data regardless of their TTL.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnswerDiff {
+    /// The answers returned by both servers, with the TTLs of the first server.
+    pub common: Vec<DnsAnswer>,
+    /// The answers returned only by the first server.
+    pub only_a: Vec<DnsAnswer>,
+    /// The answers returned only by the second server.
+    pub only_b: Vec<DnsAnswer>,
+}
+
+/// An answer along with the URI of the server that returned it, as returned by
+/// [Dns::resolve_attributed].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributedAnswer {
+    /// The answer.
+    pub answer: DnsAnswer,
+    /// The URI of the server that returned the answer.
+    pub server: String,
+}
+
+/// The answers to a query along with the name and record type that were queried, as
+/// returned by [Dns::resolve_issued].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuedQuery {
+    /// The name that was queried after its conversion to ASCII, without a trailing dot.
+    pub issued_name: String,
+    /// The record type that was queried.
+    pub issued_type: u32,
+    /// The answers of the requested record type.
+    pub answers: Vec<DnsAnswer>,
+}
+
+/// The addresses of a host by address family, as returned by [Dns::resolve_host_detailed].
+/// Each family has its own result so that the failure of one is not hidden by the other.
+#[derive(Debug)]
+pub struct HostResult {
+    /// The IPv4 addresses from the `A` records of the host.
+    pub v4: Result<Vec<std::net::Ipv4Addr>, error::DnsError>,
+    /// The IPv6 addresses from the `AAAA` records of the host.
+    pub v6: Result<Vec<std::net::Ipv6Addr>, error::DnsError>,
+}
+
 #[allow(non_snake_case)]
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 struct DnsResponse {
     Status: u32,
+    #[serde(default)]
+    TC: bool,
+    #[serde(default)]
+    RD: bool,
+    #[serde(default)]
+    RA: bool,
+    #[serde(default)]
+    AD: bool,
+    #[serde(default)]
+    CD: bool,
     Answer: Option<Vec<DnsAnswer>>,
+    Authority: Option<Vec<DnsAnswer>>,
+    #[serde(default, deserialize_with = "deserialize_comment")]
     Comment: Option<String>,
 }
 
+// Reads the comment of a response, which Google returns as a string on some endpoints and as
+// a list of strings on others. A list is joined with spaces.
+fn deserialize_comment<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Comment {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(
+        <Option<Comment> as serde::Deserialize>::deserialize(deserializer)?.map(|c| match c {
+            Comment::One(comment) => comment,
+            Comment::Many(comments) => comments.join(" "),
+        }),
+    )
+}
+
 pub trait DnsHttpsServer: Clone {
+    /// The URI of the DoH endpoint such as `https://dns.google/resolve`. It may include a
+    /// path and query parameters of its own, in which case the query is appended to them.
     fn uri(&self) -> &str;
+    /// The timeout of requests to this server. A zero timeout stands for the default timeout
+    /// set with [Dns::with_default_timeout].
     fn timeout(&self) -> Duration;
+
+    /// The name of the query parameter through which the server accepts the EDNS0 UDP buffer
+    /// size set with [Dns::with_edns_buffer_size]. By default servers are assumed not to
+    /// support it, which is the case for Google and Cloudflare, and the size is not sent.
+    fn edns_buffer_size_param(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether the server answers queries for `ANY` records. When it does not, the `ANY`
+    /// queries of [Dns::resolve_any] sent to it query the common record types instead and
+    /// merge their answers. By default servers are assumed to support it.
+    fn supports_any(&self) -> bool {
+        true
+    }
+
+    /// Query parameters that are added to every query to this server, such as Google's `ct`.
+    /// They are appended to the URL as given so they must already be URL encoded. By default
+    /// there are none.
+    fn extra_query_params(&self) -> &[(String, String)] {
+        &[]
+    }
 }
 
+// Callback invoked when a query fails on all servers.
+type ExhaustedCallback = dyn Fn(&str, &[(String, error::QueryError)]) + Send + Sync;
+
+// Transform applied to the answers of a query before they are returned.
+type AnswerTransform = dyn Fn(&str, u32, Vec<DnsAnswer>) -> Vec<DnsAnswer> + Send + Sync;
+
 /// The main interface to this library. It provides all functions to query records.
 pub struct Dns<C: client::DnsClient, S: DnsHttpsServer> {
     client: C,
     servers: Vec<S>,
+    lenient: bool,
+    timer: Option<Box<dyn timer::Timer>>,
+    retryable_statuses: Vec<u16>,
+    per_server_retries: u32,
+    stop_on: Option<Vec<status::RCode>>,
+    single_flight:
+        Option<single_flight::SingleFlight<(String, u32), Result<DnsResponse, error::QueryError>>>,
+    on_exhausted: Option<Box<ExhaustedCallback>>,
+    transform: Option<Box<AnswerTransform>>,
+    counters: stats::Counters,
+    edns_buffer_size: Option<u16>,
+    cache: Option<cache::Cache>,
+    cache_ttl_bounds: (Duration, Duration),
+    min_cacheable_ttl: Duration,
+    strategy: strategy::Strategy,
+    latencies: strategy::Latencies,
+    breaker: Option<breaker::CircuitBreaker>,
+    breaker_window: Duration,
+    race_accepts_empty: bool,
+    flags: DnsFlags,
+    local_names: Vec<String>,
+    max_answers: Option<usize>,
+    idna: IdnaConfig,
+    keep_cnames: bool,
+    json_post: bool,
+    default_timeout: Duration,
+    empty_as_error: bool,
+    log_after_failures: usize,
+    strict_name_match: bool,
+    // The index of the last server that answered, if sticky servers are enabled.
+    sticky_server: Option<std::sync::atomic::AtomicUsize>,
+    headers: hyper::header::HeaderMap,
+    max_redirects: usize,
+    rng: std::sync::Mutex<rand::rngs::StdRng>,
+    #[cfg(feature = "system-fallback")]
+    system_fallback: bool,
+    #[cfg(feature = "tokio")]
+    concurrency: Option<tokio::sync::Semaphore>,
 }
@@ -0,0 +1,778 @@
+//! Parsing of record data returned by DoH servers into more convenient forms.
+//!
+//! The parsers only depend on [DnsAnswer] and not on any HTTP client, so they are available
+//! without the `hyper-client` feature and can be used on answers obtained with any
+//! transport, such as answers deserialized from the JSON response of a server.
+use crate::error::ParseError;
+use crate::rtype::RecordType;
+use crate::DnsAnswer;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The records commonly needed about a domain, as returned by
+/// [Dns::resolve_common](crate::Dns::resolve_common).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DomainRecords {
+    /// The IPv4 addresses of the domain.
+    pub a: Vec<Ipv4Addr>,
+    /// The IPv6 addresses of the domain.
+    pub aaaa: Vec<Ipv6Addr>,
+    /// The mail exchanges of the domain in order of preference.
+    pub mx: Vec<MxRecord>,
+    /// The text records of the domain, unquoted and concatenated.
+    pub txt: Vec<String>,
+    /// The sorted hostnames of the name servers of the domain.
+    pub ns: Vec<String>,
+}
+
+/// A mail exchange record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MxRecord {
+    /// The preference of this mail exchange. Lower values are preferred.
+    pub preference: u16,
+    /// The hostname of the mail exchange.
+    pub exchange: String,
+}
+
+impl MxRecord {
+    // Parses record data in the form `preference exchange`.
+    pub(crate) fn parse(data: &str) -> Option<MxRecord> {
+        let mut parts = data.split_ascii_whitespace();
+        let preference = parts.next()?.parse::<u16>().ok()?;
+        let exchange = parts.next()?.to_string();
+        Some(MxRecord {
+            preference,
+            exchange,
+        })
+    }
+}
+
+/// An IPsec keying material record as defined in RFC 4025.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpseckeyRecord {
+    /// The precedence of this record. Lower values are preferred.
+    pub precedence: u8,
+    /// The type of the gateway: 0 for none, 1 for an IPv4 address, 2 for an IPv6 address
+    /// and 3 for a domain name.
+    pub gateway_type: u8,
+    /// The public key algorithm: 0 for none, 1 for DSA, 2 for RSA and 3 for ECDSA.
+    pub algorithm: u8,
+    /// The gateway to which an IPsec tunnel may be created. It is `.` if there is none.
+    pub gateway: String,
+    /// The Base64 encoded public key. It is empty if there is none.
+    pub public_key: String,
+}
+
+impl IpseckeyRecord {
+    // Parses record data in the form `precedence gateway-type algorithm gateway public-key`.
+    pub(crate) fn parse(data: &str) -> Option<IpseckeyRecord> {
+        let mut parts = data.split_ascii_whitespace();
+        let precedence = parts.next()?.parse::<u8>().ok()?;
+        let gateway_type = parts.next()?.parse::<u8>().ok()?;
+        let algorithm = parts.next()?.parse::<u8>().ok()?;
+        let gateway = parts.next()?.to_string();
+        // The key may be split in several parts.
+        let public_key = parts.collect::<String>();
+        Some(IpseckeyRecord {
+            precedence,
+            gateway_type,
+            algorithm,
+            gateway,
+            public_key,
+        })
+    }
+}
+
+/// A certificate record as defined in RFC 4398.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertRecord {
+    /// The type of certificate such as 1 for X.509 or 3 for OpenPGP.
+    pub cert_type: u16,
+    /// The key tag of the certificate.
+    pub key_tag: u16,
+    /// The algorithm of the certificate key.
+    pub algorithm: u8,
+    /// The Base64 encoded certificate or CRL.
+    pub certificate: String,
+}
+
+impl CertRecord {
+    // Parses record data in the form `type key-tag algorithm certificate`. The type may be
+    // given by its mnemonic.
+    pub(crate) fn parse(data: &str) -> Option<CertRecord> {
+        let mut parts = data.split_ascii_whitespace();
+        let cert_type = parts.next()?;
+        let cert_type = match cert_type.parse::<u16>() {
+            Ok(cert_type) => cert_type,
+            Err(_) => match cert_type.to_ascii_uppercase().as_ref() {
+                "PKIX" => 1,
+                "SPKI" => 2,
+                "PGP" => 3,
+                "IPKIX" => 4,
+                "ISPKI" => 5,
+                "IPGP" => 6,
+                "ACPKIX" => 7,
+                "IACPKIX" => 8,
+                "URI" => 253,
+                "OID" => 254,
+                _ => return None,
+            },
+        };
+        let key_tag = parts.next()?.parse::<u16>().ok()?;
+        let algorithm = parts.next()?.parse::<u8>().ok()?;
+        // The certificate may be split in several parts.
+        let certificate = parts.collect::<String>();
+        Some(CertRecord {
+            cert_type,
+            key_tag,
+            algorithm,
+            certificate,
+        })
+    }
+}
+
+/// A delegation signer record as defined in RFC 4034. It is also used for child DS (`CDS`)
+/// records.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DsRecord {
+    /// The key tag of the referenced DNSKEY.
+    pub key_tag: u16,
+    /// The algorithm of the referenced DNSKEY.
+    pub algorithm: u8,
+    /// The algorithm used to construct the digest.
+    pub digest_type: u8,
+    /// The hexadecimal digest of the referenced DNSKEY.
+    pub digest: String,
+}
+
+impl DsRecord {
+    // Parses record data in the form `key-tag algorithm digest-type digest`.
+    pub(crate) fn parse(data: &str) -> Option<DsRecord> {
+        let mut parts = data.split_ascii_whitespace();
+        let key_tag = parts.next()?.parse::<u16>().ok()?;
+        let algorithm = parts.next()?.parse::<u8>().ok()?;
+        let digest_type = parts.next()?.parse::<u8>().ok()?;
+        // The digest may be split in several parts.
+        let digest = parts.collect::<String>();
+        Some(DsRecord {
+            key_tag,
+            algorithm,
+            digest_type,
+            digest,
+        })
+    }
+}
+
+/// A next secure record as defined in RFC 4034. It proves that no names exist between its
+/// owner and the next domain, and lists the types present at its owner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NsecRecord {
+    /// The next owner name in the canonical order of the zone.
+    pub next_domain: String,
+    /// The record types present at the owner name, from the type bitmap.
+    pub types: Vec<u32>,
+}
+
+impl NsecRecord {
+    // Parses record data in the form `next-domain type...` where types are mnemonics such
+    // as `A` or `RRSIG`, or in the generic form `TYPE65534`.
+    pub(crate) fn parse(data: &str) -> Option<NsecRecord> {
+        let mut parts = data.split_ascii_whitespace();
+        let next_domain = parts.next()?.to_string();
+        let types = parts
+            .map(|name| RecordType::from_name(name).map(|rtype| rtype as u32))
+            .collect::<Option<Vec<_>>>()?;
+        Some(NsecRecord { next_domain, types })
+    }
+}
+
+/// A resource record signature as defined in RFC 4034.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RrsigRecord {
+    /// The type of the records covered by the signature.
+    pub type_covered: u32,
+    /// The algorithm of the signature.
+    pub algorithm: u8,
+    /// The number of labels in the owner name of the signed records.
+    pub labels: u8,
+    /// The TTL of the signed records as it appears in the authoritative zone.
+    pub original_ttl: u32,
+    /// The time after which the signature is no longer valid.
+    pub expiration: SystemTime,
+    /// The time before which the signature is not yet valid.
+    pub inception: SystemTime,
+    /// The key tag of the DNSKEY that validates the signature.
+    pub key_tag: u16,
+    /// The owner name of the DNSKEY that validates the signature.
+    pub signer_name: String,
+    /// The base64 signature.
+    pub signature: String,
+}
+
+impl RrsigRecord {
+    // Parses record data in the form `type-covered algorithm labels original-ttl expiration
+    // inception key-tag signer-name signature`.
+    pub(crate) fn parse(data: &str) -> Option<RrsigRecord> {
+        let mut parts = data.split_ascii_whitespace();
+        let type_covered = RecordType::from_name(parts.next()?)? as u32;
+        let algorithm = parts.next()?.parse::<u8>().ok()?;
+        let labels = parts.next()?.parse::<u8>().ok()?;
+        let original_ttl = parts.next()?.parse::<u32>().ok()?;
+        let expiration = parse_signature_time(parts.next()?)?;
+        let inception = parse_signature_time(parts.next()?)?;
+        let key_tag = parts.next()?.parse::<u16>().ok()?;
+        let signer_name = parts.next()?.to_string();
+        // The signature may be split in several parts.
+        let signature = parts.collect::<String>();
+        Some(RrsigRecord {
+            type_covered,
+            algorithm,
+            labels,
+            original_ttl,
+            expiration,
+            inception,
+            key_tag,
+            signer_name,
+            signature,
+        })
+    }
+}
+
+// Parses a signature time either in the form `YYYYMMDDHHmmSS` in UTC or as seconds since
+// the epoch, as allowed by RFC 4034.
+fn parse_signature_time(time: &str) -> Option<SystemTime> {
+    if !time.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if time.len() != 14 {
+        let secs = time.parse::<u32>().ok()?;
+        return Some(UNIX_EPOCH + Duration::from_secs(secs.into()));
+    }
+    let field = |range: std::ops::Range<usize>| time[range].parse::<u64>().ok();
+    let (year, month, day) = (field(0..4)?, field(4..6)?, field(6..8)?);
+    let (hour, minute, second) = (field(8..10)?, field(10..12)?, field(12..14)?);
+    if year < 1970
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+    // Days since the epoch of the civil date, counting years from March so that the leap
+    // day is last.
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let days = 365 * y + y / 4 - y / 100 + y / 400 + day_of_year - 719_468;
+    let secs = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// A record with its data parsed according to its type. It is obtained with
+/// [DnsAnswer::parse].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Record {
+    /// A host address.
+    A(Ipv4Addr),
+    /// An IP6 address.
+    Aaaa(Ipv6Addr),
+    /// A certification authority restriction.
+    Caa(CaaRecord),
+    /// A child delegation signer.
+    Cds(DsRecord),
+    /// A certificate.
+    Cert(CertRecord),
+    /// The canonical name for an alias.
+    Cname(String),
+    /// The target of a delegation name.
+    Dname(String),
+    /// A delegation signer.
+    Ds(DsRecord),
+    /// IPsec keying material.
+    Ipseckey(IpseckeyRecord),
+    /// A mail exchange.
+    Mx(MxRecord),
+    /// An authoritative name server.
+    Ns(String),
+    /// A next secure record.
+    Nsec(NsecRecord),
+    /// A domain name pointer.
+    Ptr(String),
+    /// A resource record signature.
+    Rrsig(RrsigRecord),
+    /// The start of a zone of authority.
+    Soa(SoaRecord),
+    /// The character strings of an SPF record.
+    Spf(Vec<String>),
+    /// A server selection.
+    Srv(SrvRecord),
+    /// The character strings of a text record.
+    Txt(Vec<String>),
+    /// Any other record type with its data as returned by the server.
+    Other(u32, String),
+}
+
+impl DnsAnswer {
+    /// Parses the data of this answer according to its type. Types without a dedicated
+    /// parser are returned as [Record::Other].
+    pub fn parse(&self) -> Result<Record, ParseError> {
+        let data = self.data.trim();
+        let record = match RecordType::from_u32(self.r#type) {
+            Some(RecordType::A) => data.parse().ok().map(Record::A),
+            Some(RecordType::AAAA) => data.parse().ok().map(Record::Aaaa),
+            Some(RecordType::CAA) => CaaRecord::parse(data).map(Record::Caa),
+            Some(RecordType::CDS) => DsRecord::parse(data).map(Record::Cds),
+            Some(RecordType::CERT) => CertRecord::parse(data).map(Record::Cert),
+            Some(RecordType::CNAME) => Some(Record::Cname(data.to_string())),
+            Some(RecordType::DNAME) => Some(Record::Dname(data.to_string())),
+            Some(RecordType::DS) => DsRecord::parse(data).map(Record::Ds),
+            Some(RecordType::IPSECKEY) => IpseckeyRecord::parse(data).map(Record::Ipseckey),
+            Some(RecordType::MX) => MxRecord::parse(data).map(Record::Mx),
+            Some(RecordType::NS) => Some(Record::Ns(data.to_string())),
+            Some(RecordType::NSEC) => NsecRecord::parse(data).map(Record::Nsec),
+            Some(RecordType::PTR) => Some(Record::Ptr(data.to_string())),
+            Some(RecordType::RRSIG) => RrsigRecord::parse(data).map(Record::Rrsig),
+            Some(RecordType::SOA) => SoaRecord::parse(data).map(Record::Soa),
+            Some(RecordType::SPF) => Some(Record::Spf(txt_chunks(data))),
+            Some(RecordType::SRV) => SrvRecord::parse(data).map(Record::Srv),
+            Some(RecordType::TXT) => Some(Record::Txt(txt_chunks(data))),
+            _ => Some(Record::Other(self.r#type, self.data.clone())),
+        };
+        record.ok_or_else(|| ParseError {
+            rtype: self.r#type,
+            data: self.data.clone(),
+        })
+    }
+}
+
+/// A certification authority authorization record as defined in RFC 8659.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaaRecord {
+    /// The flags of the record. A value of 128 marks the property as critical.
+    pub flags: u8,
+    /// The property tag such as `issue` or `iodef`.
+    pub tag: String,
+    /// The unquoted value of the property.
+    pub value: String,
+}
+
+impl CaaRecord {
+    // Parses record data in the form `flags tag "value"`.
+    pub(crate) fn parse(data: &str) -> Option<CaaRecord> {
+        let mut parts = data.trim().splitn(3, char::is_whitespace);
+        let flags = parts.next()?.parse::<u8>().ok()?;
+        let tag = parts.next()?.to_string();
+        let value = txt_to_string(parts.next().unwrap_or_default());
+        Some(CaaRecord { flags, tag, value })
+    }
+}
+
+/// A start of a zone of authority record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SoaRecord {
+    /// The primary name server of the zone.
+    pub mname: String,
+    /// The mailbox of the person responsible for the zone.
+    pub rname: String,
+    /// The version number of the zone.
+    pub serial: u32,
+    /// The interval in seconds before the zone should be refreshed.
+    pub refresh: u32,
+    /// The interval in seconds before a failed refresh should be retried.
+    pub retry: u32,
+    /// The time in seconds after which the zone is no longer authoritative.
+    pub expire: u32,
+    /// The TTL in seconds for negative responses.
+    pub minimum: u32,
+}
+
+impl SoaRecord {
+    // Parses record data in the form `mname rname serial refresh retry expire minimum`.
+    pub(crate) fn parse(data: &str) -> Option<SoaRecord> {
+        let mut parts = data.split_ascii_whitespace();
+        Some(SoaRecord {
+            mname: parts.next()?.to_string(),
+            rname: parts.next()?.to_string(),
+            serial: parts.next()?.parse().ok()?,
+            refresh: parts.next()?.parse().ok()?,
+            retry: parts.next()?.parse().ok()?,
+            expire: parts.next()?.parse().ok()?,
+            minimum: parts.next()?.parse().ok()?,
+        })
+    }
+}
+
+/// A server selection record as defined in RFC 2782.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SrvRecord {
+    /// The priority of the target. Lower values are preferred.
+    pub priority: u16,
+    /// The relative weight of targets with the same priority.
+    pub weight: u16,
+    /// The port of the service on the target.
+    pub port: u16,
+    /// The hostname of the target.
+    pub target: String,
+}
+
+impl SrvRecord {
+    // Parses record data in the form `priority weight port target`.
+    pub(crate) fn parse(data: &str) -> Option<SrvRecord> {
+        let mut parts = data.split_ascii_whitespace();
+        Some(SrvRecord {
+            priority: parts.next()?.parse().ok()?,
+            weight: parts.next()?.parse().ok()?,
+            port: parts.next()?.parse().ok()?,
+            target: parts.next()?.to_string(),
+        })
+    }
+}
+
+/// The result of an SPF mechanism that matches, given by its qualifier prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SpfQualifier {
+    /// `+`, the default when there is no qualifier.
+    Pass,
+    /// `-`.
+    Fail,
+    /// `~`.
+    SoftFail,
+    /// `?`.
+    Neutral,
+}
+
+/// The kind of an SPF mechanism as defined in RFC 7208.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SpfMechanismKind {
+    /// `all`, which always matches.
+    All,
+    /// `include:`, which matches if the SPF record of another domain passes.
+    Include,
+    /// `a`, which matches the addresses of a domain.
+    A,
+    /// `mx`, which matches the addresses of the mail exchanges of a domain.
+    Mx,
+    /// `ptr`, which matches if the reverse name of the sender is in a domain.
+    Ptr,
+    /// `ip4:`, which matches an IPv4 network.
+    Ip4,
+    /// `ip6:`, which matches an IPv6 network.
+    Ip6,
+    /// `exists:`, which matches if a domain has an `A` record.
+    Exists,
+}
+
+/// A mechanism of an SPF record, as returned by [parse_spf].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SpfMechanism {
+    /// The result if the mechanism matches.
+    pub qualifier: SpfQualifier,
+    /// The kind of the mechanism.
+    pub kind: SpfMechanismKind,
+    /// What follows the name of the mechanism without its `:` separator, such as the domain
+    /// of `include:_spf.example.com`, the network of `ip4:192.0.2.0/24` or the prefix length
+    /// of `a/24`. It is `None` if there is nothing.
+    pub value: Option<String>,
+}
+
+/// Parses an SPF record such as `v=spf1 ip4:192.0.2.0/24 include:_spf.example.com -all` into
+/// its mechanisms in order. The record is the unquoted and concatenated TXT data as returned
+/// by [Dns::resolve_txt_matching](crate::Dns::resolve_txt_matching). Modifiers such as
+/// `redirect=` are skipped. An error is returned if the record does not start with `v=spf1`
+/// or has an unknown or incomplete mechanism.
+pub fn parse_spf(record: &str) -> Result<Vec<SpfMechanism>, ParseError> {
+    let error = || ParseError {
+        rtype: RecordType::TXT as u32,
+        data: record.to_string(),
+    };
+    let mut terms = record.split_ascii_whitespace();
+    if !terms
+        .next()
+        .is_some_and(|version| version.eq_ignore_ascii_case("v=spf1"))
+    {
+        return Err(error());
+    }
+    let mut mechanisms = Vec::new();
+    for term in terms {
+        let (qualifier, term) = match term.as_bytes()[0] {
+            b'+' => (SpfQualifier::Pass, &term[1..]),
+            b'-' => (SpfQualifier::Fail, &term[1..]),
+            b'~' => (SpfQualifier::SoftFail, &term[1..]),
+            b'?' => (SpfQualifier::Neutral, &term[1..]),
+            _ => (SpfQualifier::Pass, term),
+        };
+        let split = term.find([':', '/', '=']);
+        let (name, value) = match split {
+            Some(i) => (&term[..i], Some(&term[i..])),
+            None => (term, None),
+        };
+        // A modifier is a name followed by `=`.
+        if value.is_some_and(|v| v.starts_with('=')) {
+            continue;
+        }
+        let value = value
+            .map(|v| v.strip_prefix(':').unwrap_or(v))
+            .filter(|v| !v.is_empty())
+            .map(str::to_string);
+        let kind = match name.to_ascii_lowercase().as_ref() {
+            "all" => SpfMechanismKind::All,
+            "include" => SpfMechanismKind::Include,
+            "a" => SpfMechanismKind::A,
+            "mx" => SpfMechanismKind::Mx,
+            "ptr" => SpfMechanismKind::Ptr,
+            "ip4" => SpfMechanismKind::Ip4,
+            "ip6" => SpfMechanismKind::Ip6,
+            "exists" => SpfMechanismKind::Exists,
+            _ => return Err(error()),
+        };
+        let required = match kind {
+            SpfMechanismKind::All => {
+                if value.is_some() {
+                    return Err(error());
+                }
+                false
+            }
+            SpfMechanismKind::A | SpfMechanismKind::Mx | SpfMechanismKind::Ptr => false,
+            _ => true,
+        };
+        if required && value.is_none() {
+            return Err(error());
+        }
+        mechanisms.push(SpfMechanism {
+            qualifier,
+            kind,
+            value,
+        });
+    }
+    Ok(mechanisms)
+}
+
+/// The policy of a DMARC record for mail that fails authentication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DmarcPolicy {
+    /// `none`: no action is requested.
+    None,
+    /// `quarantine`: the mail should be treated as suspicious.
+    Quarantine,
+    /// `reject`: the mail should be rejected.
+    Reject,
+}
+
+impl DmarcPolicy {
+    fn parse(value: &str) -> Option<DmarcPolicy> {
+        match value.to_ascii_lowercase().as_ref() {
+            "none" => Some(DmarcPolicy::None),
+            "quarantine" => Some(DmarcPolicy::Quarantine),
+            "reject" => Some(DmarcPolicy::Reject),
+            _ => None,
+        }
+    }
+}
+
+/// A DMARC policy record as defined in RFC 7489, as returned by
+/// [Dns::resolve_dmarc](crate::Dns::resolve_dmarc).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DmarcRecord {
+    /// The policy for the domain (`p`).
+    pub policy: DmarcPolicy,
+    /// The policy for subdomains (`sp`). The domain policy applies if it is `None`.
+    pub subdomain_policy: Option<DmarcPolicy>,
+    /// The URIs to send aggregate reports to (`rua`).
+    pub rua: Vec<String>,
+    /// The URIs to send failure reports to (`ruf`).
+    pub ruf: Vec<String>,
+    /// The percentage of mail the policy applies to (`pct`). It is 100 by default.
+    pub pct: u8,
+    /// Whether DKIM identifiers must match the domain exactly (`adkim=s`) rather than share
+    /// its organizational domain.
+    pub strict_dkim: bool,
+    /// Whether SPF identifiers must match the domain exactly (`aspf=s`) rather than share its
+    /// organizational domain.
+    pub strict_spf: bool,
+}
+
+impl DmarcRecord {
+    // Parses a record in the form `v=DMARC1; p=policy; tag=value; ...` from unquoted and
+    // concatenated TXT data. Unknown tags are ignored.
+    pub(crate) fn parse(data: &str) -> Option<DmarcRecord> {
+        let mut tags = data
+            .split(';')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(|tag| match tag.find('=') {
+                Some(i) => (tag[..i].trim(), tag[i + 1..].trim()),
+                None => (tag, ""),
+            });
+        if tags.next() != Some(("v", "DMARC1")) {
+            return None;
+        }
+        let uris = |value: &str| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|uri| !uri.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let mut policy = None;
+        let mut record = DmarcRecord {
+            policy: DmarcPolicy::None,
+            subdomain_policy: None,
+            rua: Vec::new(),
+            ruf: Vec::new(),
+            pct: 100,
+            strict_dkim: false,
+            strict_spf: false,
+        };
+        for (tag, value) in tags {
+            match tag.to_ascii_lowercase().as_ref() {
+                "p" => policy = Some(DmarcPolicy::parse(value)?),
+                "sp" => record.subdomain_policy = Some(DmarcPolicy::parse(value)?),
+                "rua" => record.rua = uris(value),
+                "ruf" => record.ruf = uris(value),
+                "pct" => record.pct = value.parse::<u8>().ok().filter(|&pct| pct <= 100)?,
+                "adkim" => record.strict_dkim = value.eq_ignore_ascii_case("s"),
+                "aspf" => record.strict_spf = value.eq_ignore_ascii_case("s"),
+                _ => {}
+            }
+        }
+        record.policy = policy?;
+        Some(record)
+    }
+}
+
+/// The scope of an IPv6 address, as returned by [classify_ipv6]. It tells which addresses
+/// returned by `AAAA` records can be reached from the internet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Ipv6Class {
+    /// A global unicast address (`2000::/3`), routable on the internet.
+    GlobalUnicast,
+    /// A unique local address (`fc00::/7`) as defined in RFC 4193, only routable within a
+    /// site.
+    UniqueLocal,
+    /// A link-local address (`fe80::/10`), only reachable on the same link.
+    LinkLocal,
+    /// An address reserved for documentation (`2001:db8::/32`) as defined in RFC 3849.
+    Documentation,
+    /// A multicast address (`ff00::/8`).
+    Multicast,
+    /// The loopback address (`::1`).
+    Loopback,
+    /// The unspecified address (`::`).
+    Unspecified,
+    /// Any other address, such as an IPv4-mapped address.
+    Other,
+}
+
+impl Ipv6Class {
+    /// Whether addresses of this class are routable on the internet.
+    pub fn is_global(&self) -> bool {
+        *self == Ipv6Class::GlobalUnicast
+    }
+}
+
+/// Returns the scope of the given IPv6 address. This allows ignoring link-local or unique
+/// local addresses returned by `AAAA` records.
+pub fn classify_ipv6(addr: &Ipv6Addr) -> Ipv6Class {
+    let segments = addr.segments();
+    if addr.is_unspecified() {
+        Ipv6Class::Unspecified
+    } else if addr.is_loopback() {
+        Ipv6Class::Loopback
+    } else if segments[0] == 0x2001 && segments[1] == 0x0db8 {
+        Ipv6Class::Documentation
+    } else if segments[0] & 0xe000 == 0x2000 {
+        Ipv6Class::GlobalUnicast
+    } else if segments[0] & 0xfe00 == 0xfc00 {
+        Ipv6Class::UniqueLocal
+    } else if segments[0] & 0xffc0 == 0xfe80 {
+        Ipv6Class::LinkLocal
+    } else if segments[0] & 0xff00 == 0xff00 {
+        Ipv6Class::Multicast
+    } else {
+        Ipv6Class::Other
+    }
+}
+
+/// Formats the answers as lines of a BIND zone file in the form `name TTL IN TYPE data`.
+/// Record types without a mnemonic are written as `TYPE` followed by their value as
+/// described in RFC 3597.
+pub fn to_zone_file(answers: &[DnsAnswer]) -> String {
+    answers
+        .iter()
+        .map(|a| {
+            let rtype = match RecordType::from_u32(a.r#type) {
+                Some(rtype) => rtype.name().to_string(),
+                None => format!("TYPE{}", a.r#type),
+            };
+            format!("{}\t{}\tIN\t{}\t{}\n", a.name, a.TTL, rtype, a.data)
+        })
+        .collect()
+}
+
+/// Converts TXT record data into a single string. Servers return TXT data as one or more
+/// quoted character strings such as `"v=spf1 " "-all"`. These are unquoted, unescaped and
+/// concatenated. Data that is not quoted is returned as is.
+pub(crate) fn txt_to_string(data: &str) -> String {
+    String::from_utf8_lossy(&txt_chunk_bytes(data).concat()).into_owned()
+}
+
+/// Converts TXT record data into its unquoted and unescaped character strings.
+pub(crate) fn txt_chunks(data: &str) -> Vec<String> {
+    txt_chunk_bytes(data)
+        .iter()
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect()
+}
+
+// Splits TXT record data into its character strings.
+fn txt_chunk_bytes(data: &str) -> Vec<Vec<u8>> {
+    let data = data.trim();
+    if !data.starts_with('"') {
+        return vec![data.as_bytes().to_vec()];
+    }
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::new();
+    let mut bytes = data.bytes();
+    let mut quoted = false;
+    while let Some(b) = bytes.next() {
+        match b {
+            b'"' if quoted => {
+                chunks.push(std::mem::take(&mut chunk));
+                quoted = false;
+            }
+            b'"' => quoted = true,
+            b'\\' => match bytes.next() {
+                // A `\DDD` escape is a decimal byte value.
+                Some(d) if d.is_ascii_digit() => {
+                    let mut value = u32::from(d - b'0');
+                    for _ in 0..2 {
+                        match bytes.clone().next() {
+                            Some(d) if d.is_ascii_digit() => {
+                                value = value * 10 + u32::from(d - b'0');
+                                bytes.next();
+                            }
+                            _ => break,
+                        }
+                    }
+                    chunk.push(value as u8);
+                }
+                Some(c) => chunk.push(c),
+                None => {}
+            },
+            // Whitespace between character strings is not part of the text.
+            b if !quoted && b.is_ascii_whitespace() => {}
+            b => chunk.push(b),
+        }
+    }
+    // An unterminated character string.
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
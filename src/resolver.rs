@@ -0,0 +1,55 @@
+//! Adapter exposing a [Dns](crate::Dns) instance through the interface of common resolvers.
+use crate::client::DnsClient;
+use crate::error::DnsError;
+use crate::{Dns, DnsHttpsServer};
+use futures_util::try_join;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+/// A resolver with a `lookup_ip` method like the resolvers of `hickory-dns`, so that this
+/// crate can be used where such a resolver is expected. It shares the [Dns] instance it is
+/// created with and is cheap to clone.
+pub struct DnsResolver<C: DnsClient, S: DnsHttpsServer> {
+    dns: Arc<Dns<C, S>>,
+}
+
+impl<C: DnsClient, S: DnsHttpsServer> DnsResolver<C, S> {
+    /// Creates a resolver that queries with the given instance.
+    pub fn new(dns: Dns<C, S>) -> Self {
+        DnsResolver { dns: Arc::new(dns) }
+    }
+
+    /// Returns the instance the resolver queries with.
+    pub fn dns(&self) -> &Dns<C, S> {
+        &self.dns
+    }
+
+    /// Returns the IPv4 and IPv6 addresses of the given host, with the IPv4 addresses first.
+    /// The `A` and `AAAA` records are queried concurrently. A host that is already an IP
+    /// address is returned as is without any query.
+    pub async fn lookup_ip(&self, host: impl AsRef<str>) -> Result<Vec<IpAddr>, DnsError> {
+        let host = host.as_ref();
+        if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse() {
+            return Ok(vec![ip]);
+        }
+        let (a, aaaa) = try_join!(self.dns.resolve_a(host), self.dns.resolve_aaaa(host))?;
+        Ok(a.iter()
+            .chain(aaaa.iter())
+            .filter_map(|answer| answer.data.trim().parse().ok())
+            .collect())
+    }
+}
+
+impl<C: DnsClient, S: DnsHttpsServer> Clone for DnsResolver<C, S> {
+    fn clone(&self) -> Self {
+        DnsResolver {
+            dns: self.dns.clone(),
+        }
+    }
+}
+
+impl<C: DnsClient, S: DnsHttpsServer> From<Dns<C, S>> for DnsResolver<C, S> {
+    fn from(dns: Dns<C, S>) -> Self {
+        DnsResolver::new(dns)
+    }
+}
@@ -0,0 +1,247 @@
+//! Record types that can be returned by DNS over HTTPS servers.
+use std::fmt;
+
+macro_rules! record_types {
+    (
+        $(
+            $(#[$docs:meta])*
+            ($variant:ident, $num:expr, $name:expr);
+        )+
+    ) => {
+        /// All DNS record types including those that cannot be queried with this library such
+        /// as `OPT`. These types were obtained from
+        /// <https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#dns-parameters-4>.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum RecordType {
+            $(
+                $(#[$docs])*
+                $variant = $num,
+            )+
+        }
+
+        impl RecordType {
+            /// Returns the record type with the given value if it is known.
+            pub fn from_u32(rtype: u32) -> Option<RecordType> {
+                match rtype {
+                    $(
+                    $num => Some(RecordType::$variant),
+                    )+
+                    _ => None,
+                }
+            }
+
+            /// Returns the record type with the given mnemonic such as `aaaa` or `NSAP-PTR`,
+            /// in any letter case, if it is known. The generic form of RFC 3597 such as
+            /// `TYPE28` is also accepted.
+            pub fn from_name(name: &str) -> Option<RecordType> {
+                $(
+                if name.eq_ignore_ascii_case($name) {
+                    return Some(RecordType::$variant);
+                }
+                )+
+                match name.get(..4) {
+                    Some(prefix) if prefix.eq_ignore_ascii_case("TYPE") => {
+                        name[4..].parse().ok().and_then(RecordType::from_u32)
+                    }
+                    _ => None,
+                }
+            }
+
+            /// Returns the mnemonic of the record type such as `AAAA` or `NSAP-PTR`.
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    $(
+                    RecordType::$variant => $name,
+                    )+
+                }
+            }
+        }
+    };
+}
+
+record_types! {
+    /// A host address.
+    (A, 1, "A");
+    /// An authoritative name server.
+    (NS, 2, "NS");
+    /// A mail destination. Obsolete.
+    (MD, 3, "MD");
+    /// A mail forwarder. Obsolete.
+    (MF, 4, "MF");
+    /// The canonical name for an alias.
+    (CNAME, 5, "CNAME");
+    /// Marks the start of a zone of authority.
+    (SOA, 6, "SOA");
+    /// A mailbox domain name.
+    (MB, 7, "MB");
+    /// A mail group member.
+    (MG, 8, "MG");
+    /// A mail rename domain name.
+    (MR, 9, "MR");
+    /// A null RR.
+    (NULL, 10, "NULL");
+    /// A well known service description.
+    (WKS, 11, "WKS");
+    /// A domain name pointer.
+    (PTR, 12, "PTR");
+    /// Host information.
+    (HINFO, 13, "HINFO");
+    /// Mailbox or mail list information.
+    (MINFO, 14, "MINFO");
+    /// Mail exchange.
+    (MX, 15, "MX");
+    /// Text strings.
+    (TXT, 16, "TXT");
+    /// Responsible person.
+    (RP, 17, "RP");
+    /// AFS database location.
+    (AFSDB, 18, "AFSDB");
+    /// X.25 PSDN address.
+    (X25, 19, "X25");
+    /// ISDN address.
+    (ISDN, 20, "ISDN");
+    /// Route through.
+    (RT, 21, "RT");
+    /// NSAP style A record.
+    (NSAP, 22, "NSAP");
+    /// Domain name pointer, NSAP style.
+    (NSAPPTR, 23, "NSAP-PTR");
+    /// Security signature.
+    (SIG, 24, "SIG");
+    /// Security key.
+    (KEY, 25, "KEY");
+    /// X.400 mail mapping information.
+    (PX, 26, "PX");
+    /// Geographical position.
+    (GPOS, 27, "GPOS");
+    /// IP6 address.
+    (AAAA, 28, "AAAA");
+    /// Location information.
+    (LOC, 29, "LOC");
+    /// Next domain. Obsolete.
+    (NXT, 30, "NXT");
+    /// Endpoint identifier.
+    (EID, 31, "EID");
+    /// Nimrod locator.
+    (NIMLOC, 32, "NIMLOC");
+    /// Server selection.
+    (SRV, 33, "SRV");
+    /// ATM address.
+    (ATMA, 34, "ATMA");
+    /// Naming authority pointer.
+    (NAPTR, 35, "NAPTR");
+    /// Key exchanger.
+    (KX, 36, "KX");
+    /// Certificate.
+    (CERT, 37, "CERT");
+    /// IP6 address. Obsolete.
+    (A6, 38, "A6");
+    /// Delegation name.
+    (DNAME, 39, "DNAME");
+    /// Kitchen sink.
+    (SINK, 40, "SINK");
+    /// EDNS option pseudo-record.
+    (OPT, 41, "OPT");
+    /// Address prefix list.
+    (APL, 42, "APL");
+    /// Delegation signer.
+    (DS, 43, "DS");
+    /// SSH key fingerprint.
+    (SSHFP, 44, "SSHFP");
+    /// IPsec key.
+    (IPSECKEY, 45, "IPSECKEY");
+    /// Resource record signature.
+    (RRSIG, 46, "RRSIG");
+    /// Next secure record.
+    (NSEC, 47, "NSEC");
+    /// DNS key.
+    (DNSKEY, 48, "DNSKEY");
+    /// DHCP identifier.
+    (DHCID, 49, "DHCID");
+    /// Next secure record, version 3.
+    (NSEC3, 50, "NSEC3");
+    /// NSEC3 parameters.
+    (NSEC3PARAM, 51, "NSEC3PARAM");
+    /// TLSA certificate association.
+    (TLSA, 52, "TLSA");
+    /// S/MIME certificate association.
+    (SMIMEA, 53, "SMIMEA");
+    /// Host identity protocol.
+    (HIP, 55, "HIP");
+    /// Zone status information.
+    (NINFO, 56, "NINFO");
+    /// Resource key.
+    (RKEY, 57, "RKEY");
+    /// Trust anchor link.
+    (TALINK, 58, "TALINK");
+    /// Child DS.
+    (CDS, 59, "CDS");
+    /// Child DNSKEY.
+    (CDNSKEY, 60, "CDNSKEY");
+    /// OpenPGP key.
+    (OPENPGPKEY, 61, "OPENPGPKEY");
+    /// Child-to-parent synchronization.
+    (CSYNC, 62, "CSYNC");
+    /// Message digest for DNS zone.
+    (ZONEMD, 63, "ZONEMD");
+    /// General purpose service binding.
+    (SVCB, 64, "SVCB");
+    /// Service binding for HTTPS.
+    (HTTPS, 65, "HTTPS");
+    /// Sender policy framework.
+    (SPF, 99, "SPF");
+    /// Reserved by IANA.
+    (UINFO, 100, "UINFO");
+    /// Reserved by IANA.
+    (UID, 101, "UID");
+    /// Reserved by IANA.
+    (GID, 102, "GID");
+    /// Reserved by IANA.
+    (UNSPEC, 103, "UNSPEC");
+    /// Node identifier.
+    (NID, 104, "NID");
+    /// 32-bit locator.
+    (L32, 105, "L32");
+    /// 64-bit locator.
+    (L64, 106, "L64");
+    /// Locator FQDN.
+    (LP, 107, "LP");
+    /// EUI-48 address.
+    (EUI48, 108, "EUI48");
+    /// EUI-64 address.
+    (EUI64, 109, "EUI64");
+    /// Transaction key.
+    (TKEY, 249, "TKEY");
+    /// Transaction signature.
+    (TSIG, 250, "TSIG");
+    /// Incremental zone transfer.
+    (IXFR, 251, "IXFR");
+    /// Transfer of an entire zone.
+    (AXFR, 252, "AXFR");
+    /// Mailbox-related records.
+    (MAILB, 253, "MAILB");
+    /// Mail agent records. Obsolete.
+    (MAILA, 254, "MAILA");
+    /// All records.
+    (ANY, 255, "ANY");
+    /// Uniform resource identifier.
+    (URI, 256, "URI");
+    /// Certification authority restriction.
+    (CAA, 257, "CAA");
+    /// Application visibility and control.
+    (AVC, 258, "AVC");
+    /// Digital object architecture.
+    (DOA, 259, "DOA");
+    /// Automatic multicast tunneling relay.
+    (AMTRELAY, 260, "AMTRELAY");
+    /// DNSSEC trust authorities.
+    (TA, 32768, "TA");
+    /// DNSSEC lookaside validation. Obsolete.
+    (DLV, 32769, "DLV");
+}
+
+impl fmt::Display for RecordType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
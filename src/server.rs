@@ -0,0 +1,212 @@
+//! Server configuration that can be stored and loaded with `serde`.
+use crate::DnsHttpsServer;
+use std::time::Duration;
+
+/// The timeout of requests to Google in the default setup.
+pub const DEFAULT_GOOGLE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The timeout of requests to Cloudflare in the default setup.
+pub const DEFAULT_CLOUDFLARE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The servers of the default setup used by [Dns::default](crate::Dns::default): Google first
+/// and Cloudflare second, with their default timeouts. A variation of it can be built from a
+/// copy, e.g. `Dns::with_servers(&DEFAULT_SERVERS[..1])` to only use Google.
+pub const DEFAULT_SERVERS: [ServerConfig; 2] = [
+    ServerConfig {
+        kind: ServerKind::Google,
+        uri: None,
+        timeout_secs: DEFAULT_GOOGLE_TIMEOUT.as_secs(),
+        query_params: Vec::new(),
+    },
+    ServerConfig {
+        kind: ServerKind::Cloudflare,
+        uri: None,
+        timeout_secs: DEFAULT_CLOUDFLARE_TIMEOUT.as_secs(),
+        query_params: Vec::new(),
+    },
+];
+
+/// The provider of a DoH server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerKind {
+    /// Google's server at `https://dns.google/resolve`.
+    Google,
+    /// Cloudflare's server at `https://1.1.1.1/dns-query`.
+    Cloudflare,
+    /// A server at the URI given in the configuration.
+    Custom,
+}
+
+impl ServerKind {
+    /// Returns the URI of the provider's server. It is empty for [ServerKind::Custom].
+    pub fn default_uri(&self) -> &'static str {
+        match *self {
+            ServerKind::Google => "https://dns.google/resolve",
+            ServerKind::Cloudflare => "https://1.1.1.1/dns-query",
+            ServerKind::Custom => "",
+        }
+    }
+}
+
+/// A serializable DoH server configuration. It implements [DnsHttpsServer] so a list of
+/// configurations can be given directly to [Dns::with_servers](crate::Dns::with_servers).
+///
+/// For example, in JSON:
+/// ```json
+/// [
+///     { "kind": "google", "timeout_secs": 2 },
+///     { "kind": "custom", "uri": "https://doh.example.com/resolve", "timeout_secs": 10 }
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// The provider of the server.
+    pub kind: ServerKind,
+    /// The URI of the server. It overrides the provider's URI and is required for
+    /// [ServerKind::Custom].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    /// The timeout of requests to this server in seconds. If it is missing or zero, the
+    /// default timeout set with [Dns::with_default_timeout](crate::Dns::with_default_timeout)
+    /// is used.
+    #[serde(default)]
+    pub timeout_secs: u64,
+    /// Query parameters added to every query to this server, already URL encoded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub query_params: Vec<(String, String)>,
+}
+
+impl ServerConfig {
+    /// Creates the configuration of a well-known provider with the given timeout.
+    pub fn new(kind: ServerKind, timeout: Duration) -> ServerConfig {
+        ServerConfig {
+            kind,
+            uri: None,
+            timeout_secs: timeout.as_secs(),
+            query_params: Vec::new(),
+        }
+    }
+
+    /// Creates the configuration of a server at the given URI with the given timeout.
+    pub fn custom(uri: &str, timeout: Duration) -> ServerConfig {
+        ServerConfig {
+            kind: ServerKind::Custom,
+            uri: Some(uri.to_string()),
+            timeout_secs: timeout.as_secs(),
+            query_params: Vec::new(),
+        }
+    }
+
+    /// Adds a query parameter that is sent with every query to this server. The name and
+    /// value must already be URL encoded.
+    pub fn with_query_param(mut self, name: &str, value: &str) -> ServerConfig {
+        self.query_params
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+impl DnsHttpsServer for ServerConfig {
+    fn uri(&self) -> &str {
+        match self.uri {
+            Some(ref uri) => uri,
+            None => self.kind.default_uri(),
+        }
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    fn supports_any(&self) -> bool {
+        match self.kind {
+            ServerKind::Google => true,
+            ServerKind::Cloudflare => false,
+            ServerKind::Custom => WELL_KNOWN_SERVERS
+                .iter()
+                .find(|server| server.uri() == self.uri())
+                .is_none_or(|server| server.supports_any()),
+        }
+    }
+
+    fn extra_query_params(&self) -> &[(String, String)] {
+        &self.query_params
+    }
+}
+
+/// Well-known public DoH providers. A provider can be used as a server with
+/// `ServerConfig::from(WellKnownServer::Quad9)`. Not every provider answers the JSON API
+/// used by this crate on every endpoint, so it is worth checking a provider before relying
+/// on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WellKnownServer {
+    /// Google Public DNS.
+    Google,
+    /// Cloudflare's 1.1.1.1.
+    Cloudflare,
+    /// Quad9's filtering resolver.
+    Quad9,
+    /// Cisco OpenDNS.
+    OpenDns,
+    /// AdGuard DNS with its default ad blocking.
+    AdGuard,
+    /// NextDNS without a configuration profile.
+    NextDns,
+    /// Mullvad DNS without filtering.
+    Mullvad,
+}
+
+// All the well-known providers.
+const WELL_KNOWN_SERVERS: [WellKnownServer; 7] = [
+    WellKnownServer::Google,
+    WellKnownServer::Cloudflare,
+    WellKnownServer::Quad9,
+    WellKnownServer::OpenDns,
+    WellKnownServer::AdGuard,
+    WellKnownServer::NextDns,
+    WellKnownServer::Mullvad,
+];
+
+impl WellKnownServer {
+    /// Returns the URI of the provider's DoH endpoint.
+    pub fn uri(&self) -> &'static str {
+        match *self {
+            WellKnownServer::Google => ServerKind::Google.default_uri(),
+            WellKnownServer::Cloudflare => ServerKind::Cloudflare.default_uri(),
+            WellKnownServer::Quad9 => "https://dns.quad9.net:5053/dns-query",
+            WellKnownServer::OpenDns => "https://doh.opendns.com/dns-query",
+            WellKnownServer::AdGuard => "https://dns.adguard-dns.com/resolve",
+            WellKnownServer::NextDns => "https://dns.nextdns.io/dns-query",
+            WellKnownServer::Mullvad => "https://dns.mullvad.net/dns-query",
+        }
+    }
+
+    /// Returns the default timeout of requests to the provider. Google and Cloudflare use
+    /// the timeouts of the default setup.
+    pub fn default_timeout(&self) -> Duration {
+        match *self {
+            WellKnownServer::Google => DEFAULT_GOOGLE_TIMEOUT,
+            WellKnownServer::Cloudflare => DEFAULT_CLOUDFLARE_TIMEOUT,
+            _ => Duration::from_secs(5),
+        }
+    }
+
+    /// Whether the provider answers queries for `ANY` records. Only Google does, as the
+    /// others follow RFC 8482 and refuse or minimize such queries.
+    pub fn supports_any(&self) -> bool {
+        matches!(*self, WellKnownServer::Google)
+    }
+}
+
+impl From<WellKnownServer> for ServerConfig {
+    fn from(server: WellKnownServer) -> ServerConfig {
+        let timeout = server.default_timeout();
+        match server {
+            WellKnownServer::Google => ServerConfig::new(ServerKind::Google, timeout),
+            WellKnownServer::Cloudflare => ServerConfig::new(ServerKind::Cloudflare, timeout),
+            _ => ServerConfig::custom(server.uri(), timeout),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! De-duplication of concurrent identical requests.
+use futures_channel::oneshot;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+// Shares the result of an in-flight request with every concurrent request for the same key.
+pub(crate) struct SingleFlight<K, V> {
+    in_flight: Mutex<HashMap<K, Vec<oneshot::Sender<V>>>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> SingleFlight<K, V> {
+    pub(crate) fn new() -> Self {
+        SingleFlight {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Runs the request unless one for the same key is in flight, in which case its result is
+    // awaited instead. If that request is cancelled, this one is run on its own. Whether the
+    // result was shared is returned along with it.
+    pub(crate) async fn run<F, Fut>(&self, key: K, request: F) -> (V, bool)
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let waiting = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get_mut(&key) {
+                Some(waiters) => {
+                    let (sender, receiver) = oneshot::channel();
+                    waiters.push(sender);
+                    Some(receiver)
+                }
+                None => {
+                    in_flight.insert(key.clone(), Vec::new());
+                    None
+                }
+            }
+        };
+        if let Some(receiver) = waiting {
+            return match receiver.await {
+                Ok(value) => (value, true),
+                Err(_) => (request().await, false),
+            };
+        }
+        // The guard releases the waiters if this future is dropped before completing.
+        let mut guard = Guard {
+            flight: self,
+            key,
+            done: false,
+        };
+        let value = request().await;
+        for waiter in guard.finish() {
+            let _ = waiter.send(value.clone());
+        }
+        (value, false)
+    }
+}
+
+struct Guard<'a, K: Eq + Hash, V> {
+    flight: &'a SingleFlight<K, V>,
+    key: K,
+    done: bool,
+}
+
+impl<'a, K: Eq + Hash, V> Guard<'a, K, V> {
+    fn finish(&mut self) -> Vec<oneshot::Sender<V>> {
+        self.done = true;
+        let mut in_flight = self.flight.in_flight.lock().unwrap();
+        in_flight.remove(&self.key).unwrap_or_default()
+    }
+}
+
+impl<'a, K: Eq + Hash, V> Drop for Guard<'a, K, V> {
+    fn drop(&mut self) {
+        if !self.done {
+            // Dropping the senders wakes up the waiters.
+            if let Ok(mut in_flight) = self.flight.in_flight.lock() {
+                in_flight.remove(&self.key);
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Statistics about the queries made by a [Dns](crate::Dns) instance.
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A snapshot of the cumulative statistics of a [Dns](crate::Dns) instance, as returned by
+/// [Dns::stats](crate::Dns::stats).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsStats {
+    /// The number of queries sent to the servers.
+    pub queries: u64,
+    /// The number of HTTP requests made to the servers, including retries.
+    pub requests: u64,
+    /// The number of requests that retried a failed request on the same or the next server.
+    pub retries: u64,
+    /// The number of response body bytes received, before decompression.
+    pub bytes_received: u64,
+    /// The number of queries that received a response from a server.
+    pub successes: u64,
+    /// The number of queries that failed on all servers.
+    pub failures: u64,
+    /// The number of queries answered from the cache.
+    pub cache_hits: u64,
+    /// The number of queries that were not found in the cache, when it is enabled.
+    pub cache_misses: u64,
+    /// The number of queries that shared the result of an identical query in flight instead
+    /// of making their own requests, when single-flight is enabled.
+    pub coalesced: u64,
+}
+
+// Thread-safe counters behind the statistics.
+#[derive(Default)]
+pub(crate) struct Counters {
+    pub(crate) queries: AtomicU64,
+    pub(crate) requests: AtomicU64,
+    pub(crate) retries: AtomicU64,
+    pub(crate) bytes_received: AtomicU64,
+    pub(crate) successes: AtomicU64,
+    pub(crate) failures: AtomicU64,
+    pub(crate) cache_hits: AtomicU64,
+    pub(crate) cache_misses: AtomicU64,
+    pub(crate) coalesced: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) fn add(counter: &AtomicU64, value: u64) {
+        counter.fetch_add(value, Ordering::Relaxed);
+    }
+
+    // Counts the end of a query.
+    pub(crate) fn query_done(&self, success: bool) {
+        if success {
+            Counters::add(&self.successes, 1);
+        } else {
+            Counters::add(&self.failures, 1);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> DnsStats {
+        DnsStats {
+            queries: self.queries.load(Ordering::Relaxed),
+            requests: self.requests.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            successes: self.successes.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -2,7 +2,7 @@
 use std::fmt;
 /// These codes were obtained from
 /// <https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#dns-parameters-6>.
-#[derive(Debug, FromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
 pub enum RCode {
     /// No Error.
     NoError,
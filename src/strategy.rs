@@ -0,0 +1,98 @@
+//! Strategies to choose the order in which servers are tried.
+use rand::Rng;
+use std::sync::Mutex;
+use std::time::Duration;
+
+// The weight of the latest latency in the moving average of a server.
+const EWMA_ALPHA: f64 = 0.3;
+
+/// The order in which the servers of a [Dns](crate::Dns) instance are tried for each query,
+/// set with [Dns::with_strategy](crate::Dns::with_strategy). Unless stated otherwise, the
+/// remaining servers are tried in their given order if the first one fails.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Servers are always tried in the given order. This is the default.
+    #[default]
+    Sequential,
+    /// The first server is chosen at random according to the given weights, which are
+    /// parallel to the list of servers. For example, weights of `[80, 20]` send 80% of
+    /// queries to the first server. Servers without a weight have a weight of zero.
+    Weighted(Vec<u32>),
+    /// Servers are tried from the lowest to the highest average response time over past
+    /// queries, with failed requests counted as taking the server timeout. Servers without a
+    /// response time yet are tried first so that every server is measured.
+    Adaptive,
+}
+
+impl Strategy {
+    // Returns the indexes of the servers in the order they should be tried.
+    pub(crate) fn order(
+        &self,
+        servers: usize,
+        latencies: &Latencies,
+        rng: &mut impl Rng,
+    ) -> Vec<usize> {
+        let first = match *self {
+            Strategy::Sequential => 0,
+            Strategy::Weighted(ref weights) => weighted_index(weights, servers, rng),
+            Strategy::Adaptive => return latencies.order(servers),
+        };
+        let mut order = Vec::with_capacity(servers);
+        order.push(first);
+        order.extend((0..servers).filter(|&i| i != first));
+        order
+    }
+}
+
+// Picks an index at random according to the weights. It is the first one if all weights are
+// zero.
+fn weighted_index(weights: &[u32], servers: usize, rng: &mut impl Rng) -> usize {
+    let weights = &weights[..weights.len().min(servers)];
+    let total = weights.iter().map(|&w| u64::from(w)).sum::<u64>();
+    if total == 0 {
+        return 0;
+    }
+    let mut pick = rng.gen_range(0..total);
+    for (i, &weight) in weights.iter().enumerate() {
+        if pick < u64::from(weight) {
+            return i;
+        }
+        pick -= u64::from(weight);
+    }
+    0
+}
+
+// The exponentially weighted moving average of the response time of each server.
+pub(crate) struct Latencies {
+    averages: Mutex<Vec<Option<f64>>>,
+}
+
+impl Latencies {
+    pub(crate) fn new(servers: usize) -> Self {
+        Latencies {
+            averages: Mutex::new(vec![None; servers]),
+        }
+    }
+
+    pub(crate) fn record(&self, server: usize, latency: Duration) {
+        let mut averages = self.averages.lock().unwrap();
+        let latency = latency.as_secs_f64();
+        averages[server] = Some(match averages[server] {
+            Some(average) => EWMA_ALPHA * latency + (1.0 - EWMA_ALPHA) * average,
+            None => latency,
+        });
+    }
+
+    // Returns the indexes of the servers from the fastest to the slowest. The sort is stable
+    // so servers with the same average keep their given order.
+    fn order(&self, servers: usize) -> Vec<usize> {
+        let averages = self.averages.lock().unwrap();
+        let mut order = (0..servers).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+            let a = averages.get(a).copied().flatten().unwrap_or(0.0);
+            let b = averages.get(b).copied().flatten().unwrap_or(0.0);
+            a.total_cmp(&b)
+        });
+        order
+    }
+}
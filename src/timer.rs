@@ -0,0 +1,53 @@
+//! Timers used to enforce the timeout of each server.
+use async_trait::async_trait;
+use futures_util::future::{self, Either};
+use std::future::Future;
+use std::time::Duration;
+
+/// Completes after a given duration. It allows the library to run on executors other than
+/// `tokio` by providing a timer from that executor with [Dns::with_timer](crate::Dns::with_timer).
+#[async_trait]
+pub trait Timer: Send + Sync {
+    async fn sleep(&self, duration: Duration);
+}
+
+/// Timer based on `tokio::time`. It requires the `tokio-timer` feature and must be used
+/// within a `tokio` runtime.
+#[cfg(feature = "tokio-timer")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioTimer;
+
+#[cfg(feature = "tokio-timer")]
+#[async_trait]
+impl Timer for TokioTimer {
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await
+    }
+}
+
+// Returns the default timer if one is available.
+pub(crate) fn default_timer() -> Option<Box<dyn Timer>> {
+    #[cfg(feature = "tokio-timer")]
+    return Some(Box::new(TokioTimer));
+    #[cfg(not(feature = "tokio-timer"))]
+    None
+}
+
+// Runs the given future until it completes or the duration elapses, in which case `None` is
+// returned. Without a timer, the future always runs to completion.
+pub(crate) async fn timeout<F: Future>(
+    timer: Option<&dyn Timer>,
+    duration: Duration,
+    fut: F,
+) -> Option<F::Output> {
+    match timer {
+        None => Some(fut.await),
+        Some(timer) => {
+            futures_util::pin_mut!(fut);
+            match future::select(fut, timer.sleep(duration)).await {
+                Either::Left((output, _)) => Some(output),
+                Either::Right(_) => None,
+            }
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Spans of queries and server attempts for the `tracing` feature.
+use std::future::Future;
+use std::time::Instant;
+use tracing::{Instrument, Span};
+
+// Runs the future within the given span and records its status and latency in milliseconds
+// on the span once it completes. The span has to declare the `status` and `latency_ms`
+// fields for them to be recorded.
+pub(crate) async fn traced<F: Future>(
+    span: Span,
+    fut: F,
+    status: impl FnOnce(&F::Output) -> String,
+) -> F::Output {
+    let start = Instant::now();
+    let output = fut.instrument(span.clone()).await;
+    span.record("latency_ms", start.elapsed().as_millis() as u64);
+    span.record("status", status(&output).as_str());
+    output
+}
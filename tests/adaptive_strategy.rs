@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, strategy::Strategy, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Client that records the host of every request. `slow.example` answers after 50
+// milliseconds, `down.example` fails with a 500 status, and other servers answer right away.
+#[derive(Default)]
+struct LatencyClient {
+    hosts: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl DnsClient for LatencyClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let host = uri.host().unwrap_or_default().to_string();
+        self.hosts.lock().unwrap().push(host.clone());
+        let status = match host.as_str() {
+            "slow.example" => {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                200
+            }
+            "down.example" => 500,
+            _ => 200,
+        };
+        Ok(Response::builder()
+            .status(status)
+            .body(Body::from(
+                r#"{"Status":0,"Answer":[{"name":"example.com.","type":1,"TTL":300,"data":"192.0.2.1"}]}"#,
+            ))
+            .unwrap())
+    }
+}
+
+fn dns(first: &str) -> (Dns<LatencyClient, ServerConfig>, Arc<Mutex<Vec<String>>>) {
+    let servers = [
+        ServerConfig::custom(first, Duration::from_secs(1)),
+        ServerConfig::custom("https://fast.example/resolve", Duration::from_secs(1)),
+    ];
+    let client = LatencyClient::default();
+    let hosts = client.hosts.clone();
+    let dns = Dns::with_client(client, &servers)
+        .unwrap()
+        .with_strategy(Strategy::Adaptive);
+    (dns, hosts)
+}
+
+#[tokio::test]
+async fn prefers_faster_server() {
+    let (dns, hosts) = dns("https://slow.example/resolve");
+    for _ in 0..3 {
+        dns.resolve_a("example.com").await.unwrap();
+    }
+    // The slow server is measured first as the servers are tried in their given order, then
+    // the fast server is measured and preferred.
+    assert_eq!(
+        *hosts.lock().unwrap(),
+        ["slow.example", "fast.example", "fast.example"]
+    );
+}
+
+#[tokio::test]
+async fn moves_failed_server_last() {
+    let (dns, hosts) = dns("https://down.example/resolve");
+    for _ in 0..3 {
+        dns.resolve_a("example.com").await.unwrap();
+    }
+    assert_eq!(
+        *hosts.lock().unwrap(),
+        [
+            "down.example",
+            "fast.example",
+            "fast.example",
+            "fast.example"
+        ]
+    );
+}
@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use doh_dns::{
+    client::DnsClient,
+    server::{ServerConfig, ServerKind},
+    Dns,
+};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that answers each query with a record of the queried type, except `ANY` queries
+// which are refused like Cloudflare does. Google's server is down.
+#[derive(Default)]
+struct TypedClient;
+
+#[async_trait]
+impl DnsClient for TypedClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        if uri.host() == Some("dns.google") {
+            let mut res = Response::new(Body::empty());
+            *res.status_mut() = hyper::StatusCode::INTERNAL_SERVER_ERROR;
+            return Ok(res);
+        }
+        let rtype = uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .find_map(|param| param.strip_prefix("type="))
+            .unwrap_or_default();
+        let (num, data) = match rtype {
+            "a" => (1, "192.0.2.1"),
+            "aaaa" => (28, "2001:db8::1"),
+            "mx" => (15, "10 mail.example.com."),
+            "ns" => (2, "ns.example.com."),
+            "any" => return Ok(Response::new(Body::from(r#"{"Status":5}"#))),
+            _ => return Ok(Response::new(Body::from(r#"{"Status":0}"#))),
+        };
+        Ok(Response::new(Body::from(format!(
+            r#"{{"Status":0,"Answer":[{{"name":"example.com.","type":{},"TTL":300,"data":"{}"}}]}}"#,
+            num, data
+        ))))
+    }
+}
+
+#[tokio::test]
+async fn emulates_any_without_supporting_servers() {
+    let server = ServerConfig::new(ServerKind::Cloudflare, Duration::from_secs(1));
+    let dns: Dns<TypedClient, ServerConfig> = Dns::with_servers(&[server]).unwrap();
+    let answers = dns.resolve_any("example.com").await.unwrap();
+    let types = answers.iter().map(|a| a.r#type).collect::<Vec<_>>();
+    assert_eq!(types, vec![1, 28, 15, 2]);
+}
+
+#[tokio::test]
+async fn emulates_any_on_fallback_server_without_support() {
+    let servers = [
+        ServerConfig::new(ServerKind::Google, Duration::from_secs(1)),
+        ServerConfig::new(ServerKind::Cloudflare, Duration::from_secs(1)),
+    ];
+    let dns: Dns<TypedClient, ServerConfig> = Dns::with_servers(&servers).unwrap();
+    let answers = dns.resolve_any("example.com").await.unwrap();
+    let types = answers.iter().map(|a| a.r#type).collect::<Vec<_>>();
+    assert_eq!(types, vec![1, 28, 15, 2]);
+}
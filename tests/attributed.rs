@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that answers with different addresses depending on the host of the server.
+#[derive(Default)]
+struct SplitClient;
+
+#[async_trait]
+impl DnsClient for SplitClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let data = match uri.host() {
+            Some("a.example") => ["192.0.2.1", "192.0.2.2"],
+            Some("b.example") => ["192.0.2.2", "192.0.2.3"],
+            host => panic!("unexpected request to {:?}", host),
+        };
+        let body = format!(
+            r#"{{"Status":0,"Answer":[{{"name":"example.com.","type":1,"TTL":300,"data":"{}"}},{{"name":"example.com.","type":1,"TTL":300,"data":"{}"}}]}}"#,
+            data[0], data[1]
+        );
+        Ok(Response::new(Body::from(body)))
+    }
+}
+
+fn dns() -> Dns<SplitClient, ServerConfig> {
+    let servers = [
+        ServerConfig::custom("https://a.example/resolve", Duration::from_secs(1)),
+        ServerConfig::custom("https://b.example/resolve", Duration::from_secs(1)),
+    ];
+    Dns::with_servers(&servers).unwrap()
+}
+
+#[tokio::test]
+async fn attributes_answers_to_servers() {
+    let answers = dns().resolve_attributed("example.com", "a").await.unwrap();
+    let attributed = answers
+        .iter()
+        .map(|a| (a.answer.data.as_str(), a.server.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        attributed,
+        vec![
+            ("192.0.2.1", "https://a.example/resolve"),
+            ("192.0.2.2", "https://a.example/resolve"),
+            ("192.0.2.2", "https://b.example/resolve"),
+            ("192.0.2.3", "https://b.example/resolve"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn answers_local_names_without_servers() {
+    let dns = dns().with_local_names(&["localhost", ".local"]);
+    let answers = dns.resolve_attributed("localhost", "a").await.unwrap();
+    assert_eq!(answers.len(), 1);
+    assert_eq!(answers[0].answer.data, "127.0.0.1");
+    assert_eq!(answers[0].server, "");
+    assert!(dns.resolve_attributed("printer.local", "a").await.is_err());
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, error::DnsError, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that answers `A` and `MX` queries with a record of the queried type and other
+// queries without answers.
+#[derive(Default)]
+struct TypedClient;
+
+#[async_trait]
+impl DnsClient for TypedClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let rtype = uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .find_map(|param| param.strip_prefix("type="))
+            .unwrap_or_default();
+        let (num, data) = match rtype {
+            "a" => (1, "192.0.2.1"),
+            "mx" => (15, "10 mail.example.com."),
+            _ => return Ok(Response::new(Body::from(r#"{"Status":0}"#))),
+        };
+        Ok(Response::new(Body::from(format!(
+            r#"{{"Status":0,"Answer":[{{"name":"example.com.","type":{},"TTL":300,"data":"{}"}}]}}"#,
+            num, data
+        ))))
+    }
+}
+
+#[tokio::test]
+async fn resolves_batch_in_order() {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    let dns: Dns<TypedClient, ServerConfig> = Dns::with_servers(&[server]).unwrap();
+    let results = dns
+        .resolve_batch(&[
+            ("example.com", "MX"),
+            ("example.com", "bogus"),
+            ("example.com", "a"),
+            ("example.com", "txt"),
+        ])
+        .await;
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].as_ref().unwrap()[0].data, "10 mail.example.com.");
+    assert!(matches!(results[1], Err(DnsError::InvalidRecordType)));
+    assert_eq!(results[2].as_ref().unwrap()[0].data, "192.0.2.1");
+    assert!(results[3].as_ref().unwrap().is_empty());
+}
@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Client that counts requests and answers `short.example` with a TTL of 1 second, and
+// other names with a TTL of 300 seconds.
+#[derive(Default)]
+struct TtlClient {
+    requests: Arc<Mutex<usize>>,
+}
+
+#[async_trait]
+impl DnsClient for TtlClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        *self.requests.lock().unwrap() += 1;
+        let query = uri.query().unwrap_or_default();
+        let (name, ttl) = if query.contains("short.example") {
+            ("short.example.", 1)
+        } else {
+            ("long.example.", 300)
+        };
+        Ok(Response::new(Body::from(format!(
+            r#"{{"Status":0,"Answer":[{{"name":"{}","type":1,"TTL":{},"data":"192.0.2.1"}}]}}"#,
+            name, ttl
+        ))))
+    }
+}
+
+fn dns(min: Duration, max: Duration) -> (Dns<TtlClient, ServerConfig>, Arc<Mutex<usize>>) {
+    let servers = [ServerConfig::custom(
+        "https://up.example/resolve",
+        Duration::from_secs(1),
+    )];
+    let client = TtlClient::default();
+    let requests = client.requests.clone();
+    let dns = Dns::with_client(client, &servers)
+        .unwrap()
+        .with_cache(true)
+        .with_cache_ttl_bounds(min, max);
+    (dns, requests)
+}
+
+#[tokio::test]
+async fn answers_repeated_queries_from_cache() {
+    let (dns, requests) = dns(Duration::from_secs(0), Duration::from_secs(3600));
+    dns.resolve_a("long.example").await.unwrap();
+    let answers = dns.resolve_a("long.example").await.unwrap();
+    assert_eq!(answers[0].data, "192.0.2.1");
+    assert_eq!(*requests.lock().unwrap(), 1);
+    let stats = dns.stats();
+    assert_eq!((stats.cache_hits, stats.cache_misses), (1, 1));
+}
+
+#[tokio::test]
+async fn keeps_short_ttls_for_lower_bound() {
+    let (dns, requests) = dns(Duration::from_secs(60), Duration::from_secs(3600));
+    dns.resolve_a("short.example").await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    dns.resolve_a("short.example").await.unwrap();
+    assert_eq!(*requests.lock().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn expires_long_ttls_at_upper_bound() {
+    let (dns, requests) = dns(Duration::from_secs(0), Duration::from_millis(20));
+    dns.resolve_a("long.example").await.unwrap();
+    dns.resolve_a("long.example").await.unwrap();
+    assert_eq!(*requests.lock().unwrap(), 1);
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    dns.resolve_a("long.example").await.unwrap();
+    assert_eq!(*requests.lock().unwrap(), 2);
+}
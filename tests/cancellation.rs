@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, Dns, DnsHttpsServer};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static DROPPED: AtomicBool = AtomicBool::new(false);
+
+// Sets a flag when the in-flight request is dropped.
+struct RequestGuard;
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        DROPPED.store(true, Ordering::SeqCst);
+    }
+}
+
+// Client whose requests never complete.
+#[derive(Default)]
+struct PendingClient;
+
+#[async_trait]
+impl DnsClient for PendingClient {
+    async fn get(&self, _uri: Uri) -> HyperResult<Response<Body>> {
+        let _guard = RequestGuard;
+        STARTED.store(true, Ordering::SeqCst);
+        futures_util::future::pending().await
+    }
+}
+
+#[derive(Clone)]
+struct Server;
+
+impl DnsHttpsServer for Server {
+    fn uri(&self) -> &str {
+        "https://dns.example/resolve"
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(3600)
+    }
+}
+
+#[tokio::test]
+async fn dropping_resolve_aborts_request() {
+    let dns: Dns<PendingClient, Server> = Dns::with_servers(&[Server]).unwrap();
+    let res = tokio::time::timeout(Duration::from_millis(50), dns.resolve_a("example.com")).await;
+    assert!(res.is_err());
+    assert!(STARTED.load(Ordering::SeqCst));
+    assert!(DROPPED.load(Ordering::SeqCst));
+}
@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Client that records the host of every request. `down.example` fails with a 500 status and
+// other servers answer with an address.
+#[derive(Default)]
+struct RecordingClient {
+    hosts: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl DnsClient for RecordingClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let host = uri.host().unwrap_or_default().to_string();
+        let status = if host == "down.example" { 500 } else { 200 };
+        self.hosts.lock().unwrap().push(host);
+        Ok(Response::builder()
+            .status(status)
+            .body(Body::from(
+                r#"{"Status":0,"Answer":[{"name":"example.com.","type":1,"TTL":300,"data":"192.0.2.1"}]}"#,
+            ))
+            .unwrap())
+    }
+}
+
+fn dns() -> (Dns<RecordingClient, ServerConfig>, Arc<Mutex<Vec<String>>>) {
+    let servers = [
+        ServerConfig::custom("https://down.example/resolve", Duration::from_secs(1)),
+        ServerConfig::custom("https://up.example/resolve", Duration::from_secs(1)),
+    ];
+    let client = RecordingClient::default();
+    let hosts = client.hosts.clone();
+    let dns = Dns::with_client(client, &servers)
+        .unwrap()
+        .with_circuit_breaker(2, Duration::from_secs(60));
+    (dns, hosts)
+}
+
+fn down_requests(hosts: &Mutex<Vec<String>>) -> usize {
+    hosts
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|host| *host == "down.example")
+        .count()
+}
+
+#[tokio::test]
+async fn skips_server_after_failures_within_window() {
+    let (dns, hosts) = dns();
+    for _ in 0..4 {
+        dns.resolve_a("example.com").await.unwrap();
+    }
+    assert_eq!(down_requests(&hosts), 2);
+}
+
+#[tokio::test]
+async fn forgets_failures_older_than_window() {
+    let (dns, hosts) = dns();
+    let dns = dns.with_circuit_breaker_window(Duration::from_millis(10));
+    for _ in 0..4 {
+        dns.resolve_a("example.com").await.unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(down_requests(&hosts), 4);
+}
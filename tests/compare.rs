@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns, DnsAnswer};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that answers with different addresses depending on the host of the server.
+#[derive(Default)]
+struct SplitClient;
+
+#[async_trait]
+impl DnsClient for SplitClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let (data, ttl) = match uri.host() {
+            Some("a.example") => (["192.0.2.1", "192.0.2.2"], 300),
+            _ => (["192.0.2.2", "192.0.2.3"], 60),
+        };
+        let body = format!(
+            r#"{{"Status":0,"Answer":[{{"name":"example.com.","type":1,"TTL":{ttl},"data":"{}"}},{{"name":"example.com.","type":1,"TTL":{ttl},"data":"{}"}}]}}"#,
+            data[0], data[1]
+        );
+        Ok(Response::new(Body::from(body)))
+    }
+}
+
+fn answer(data: &str, ttl: u32) -> DnsAnswer {
+    DnsAnswer {
+        name: "example.com.".to_string(),
+        r#type: 1,
+        TTL: ttl,
+        data: data.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn compares_answers_of_two_servers() {
+    let server_a = ServerConfig::custom("https://a.example/resolve", Duration::from_secs(1));
+    let server_b = ServerConfig::custom("https://b.example/resolve", Duration::from_secs(1));
+    let dns: Dns<SplitClient, ServerConfig> =
+        Dns::with_servers(std::slice::from_ref(&server_a)).unwrap();
+    let diff = dns
+        .compare("example.com", "A", &server_a, &server_b)
+        .await
+        .unwrap();
+    assert_eq!(diff.common, vec![answer("192.0.2.2", 300)]);
+    assert_eq!(diff.only_a, vec![answer("192.0.2.1", 300)]);
+    assert_eq!(diff.only_b, vec![answer("192.0.2.3", 60)]);
+}
@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// The response with one address of `example.com`, compressed with gzip.
+const GZIP_BODY: [u8; 101] = [
+    31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 13, 203, 49, 14, 128, 32, 16, 5, 209, 187, 252, 154, 108, 0,
+    43, 233, 236, 237, 180, 51, 22, 27, 221, 78, 144, 8, 70, 13, 225, 238, 210, 78, 222, 20, 76,
+    153, 243, 157, 224, 180, 194, 16, 210, 35, 23, 220, 82, 16, 216, 11, 28, 228, 101, 31, 15, 161,
+    237, 244, 4, 133, 252, 197, 86, 141, 194, 60, 143, 112, 157, 110, 207, 206, 153, 27, 52, 189,
+    37, 77, 150, 12, 234, 90, 127, 42, 123, 72, 22, 85, 0, 0, 0,
+];
+
+// The same response compressed with zlib for the `deflate` encoding.
+const DEFLATE_BODY: [u8; 89] = [
+    120, 156, 13, 203, 49, 14, 128, 32, 16, 5, 209, 187, 252, 154, 108, 0, 43, 233, 236, 237, 180,
+    51, 22, 27, 221, 78, 144, 8, 70, 13, 225, 238, 210, 78, 222, 20, 76, 153, 243, 157, 224, 180,
+    194, 16, 210, 35, 23, 220, 82, 16, 216, 11, 28, 228, 101, 31, 15, 161, 237, 244, 4, 133, 252,
+    197, 86, 141, 194, 60, 143, 112, 157, 110, 207, 206, 153, 27, 52, 189, 37, 77, 150, 12, 234,
+    90, 127, 119, 184, 24, 238,
+];
+
+// Client that answers with a body compressed with the encoding named by the host of the
+// server, such as `gzip.example`.
+#[derive(Default)]
+struct EncodingClient;
+
+#[async_trait]
+impl DnsClient for EncodingClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let (encoding, body) = match uri.host() {
+            Some("gzip.example") => ("gzip", &GZIP_BODY[..]),
+            Some("deflate.example") => ("deflate", &DEFLATE_BODY[..]),
+            _ => ("compress", &GZIP_BODY[..]),
+        };
+        Ok(Response::builder()
+            .header("Content-Encoding", encoding)
+            .body(Body::from(body.to_vec()))
+            .unwrap())
+    }
+}
+
+fn dns(host: &str) -> Dns<EncodingClient, ServerConfig> {
+    let uri = format!("https://{}/resolve", host);
+    let server = ServerConfig::custom(&uri, Duration::from_secs(1));
+    Dns::with_servers(&[server]).unwrap()
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn decodes_compressed_responses() {
+    for host in ["gzip.example", "deflate.example"] {
+        let answers = dns(host).resolve_a("example.com").await.unwrap();
+        assert_eq!(answers[0].data, "192.0.2.1");
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+#[tokio::test]
+async fn reports_compressed_responses_as_read_errors() {
+    use doh_dns::error::{DnsError, QueryError};
+    match dns("gzip.example").resolve_a("example.com").await {
+        Err(DnsError::Query(e)) => assert!(matches!(e.root(), QueryError::ReadResponse(_))),
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
+
+#[tokio::test]
+async fn reports_unsupported_encodings() {
+    use doh_dns::error::{DnsError, QueryError};
+    match dns("compress.example").resolve_a("example.com").await {
+        Err(DnsError::Query(e)) => {
+            assert!(matches!(e.root(), QueryError::ReadResponse(ref e) if e.contains("compress")))
+        }
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
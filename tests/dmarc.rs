@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client for which `_dmarc.example.com` has a DMARC policy and other names exist without
+// records.
+#[derive(Default)]
+struct DmarcClient;
+
+#[async_trait]
+impl DnsClient for DmarcClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let body = if uri
+            .query()
+            .unwrap_or_default()
+            .contains("name=_dmarc.example.com")
+        {
+            r#"{"Status":0,"Answer":[{"name":"_dmarc.example.com.","type":16,"TTL":300,"data":"\"v=DMARC1; p=reject\""}]}"#
+        } else {
+            r#"{"Status":0}"#
+        };
+        Ok(Response::new(Body::from(body)))
+    }
+}
+
+fn dns() -> Dns<DmarcClient, ServerConfig> {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    Dns::with_servers(&[server])
+        .unwrap()
+        .with_empty_as_error(true)
+}
+
+#[tokio::test]
+async fn resolves_dmarc_policy() {
+    assert!(dns().resolve_dmarc("example.com").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn returns_none_without_records_when_empty_is_error() {
+    assert!(dns().resolve_dmarc("example.org").await.unwrap().is_none());
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Client that records the host of every request. `slow.example` answers after 200
+// milliseconds and other servers answer right away, each with its own address.
+#[derive(Default)]
+struct DelayClient {
+    hosts: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl DnsClient for DelayClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let host = uri.host().unwrap_or_default().to_string();
+        self.hosts.lock().unwrap().push(host.clone());
+        let data = if host == "slow.example" {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            "192.0.2.1"
+        } else {
+            "192.0.2.2"
+        };
+        Ok(Response::new(Body::from(format!(
+            r#"{{"Status":0,"Answer":[{{"name":"example.com.","type":1,"TTL":300,"data":"{}"}}]}}"#,
+            data
+        ))))
+    }
+}
+
+#[tokio::test]
+async fn returns_answers_of_fastest_server() {
+    let servers = [
+        ServerConfig::custom("https://slow.example/resolve", Duration::from_secs(1)),
+        ServerConfig::custom("https://fast.example/resolve", Duration::from_secs(1)),
+    ];
+    let client = DelayClient::default();
+    let hosts = client.hosts.clone();
+    let dns = Dns::with_client(client, &servers).unwrap();
+    let (answers, elapsed, uri) = dns.resolve_fastest("example.com", "A").await.unwrap();
+    assert_eq!(answers[0].data, "192.0.2.2");
+    assert_eq!(uri, "https://fast.example/resolve");
+    assert!(elapsed < Duration::from_millis(200), "{:?}", elapsed);
+    // Both servers were queried at once.
+    let mut hosts = hosts.lock().unwrap().clone();
+    hosts.sort();
+    assert_eq!(hosts, ["fast.example", "slow.example"]);
+}
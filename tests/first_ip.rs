@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::net::IpAddr;
+use std::time::Duration;
+
+// Client for which `v6.example` only has an IPv6 address and other names have no address.
+#[derive(Default)]
+struct V6Client;
+
+#[async_trait]
+impl DnsClient for V6Client {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let query = uri.query().unwrap_or_default();
+        let body = if query.contains("name=v6.example") && query.contains("type=aaaa") {
+            r#"{"Status":0,"Answer":[{"name":"v6.example.","type":28,"TTL":300,"data":"2001:db8::1"}]}"#
+        } else {
+            r#"{"Status":0}"#
+        };
+        Ok(Response::new(Body::from(body)))
+    }
+}
+
+fn dns() -> Dns<V6Client, ServerConfig> {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    Dns::with_servers(&[server])
+        .unwrap()
+        .with_empty_as_error(true)
+}
+
+#[tokio::test]
+async fn falls_back_to_ipv6() {
+    assert_eq!(
+        dns().resolve_first_ip("v6.example").await.unwrap(),
+        Some("2001:db8::1".parse::<IpAddr>().unwrap())
+    );
+}
+
+#[tokio::test]
+async fn returns_none_without_addresses_when_empty_is_error() {
+    assert_eq!(dns().resolve_first_ip("none.example").await.unwrap(), None);
+}
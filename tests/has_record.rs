@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client for which `alias.example` has only a CNAME record, `nope.example` does not exist,
+// `down.example` fails with a 500 status and other names have an address.
+#[derive(Default)]
+struct NamesClient;
+
+#[async_trait]
+impl DnsClient for NamesClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let query = uri.query().unwrap_or_default();
+        let body = if query.contains("name=alias.example") {
+            r#"{"Status":0,"Answer":[{"name":"alias.example.","type":5,"TTL":300,"data":"example.com."}]}"#
+        } else if query.contains("name=nope.example") {
+            r#"{"Status":3}"#
+        } else if query.contains("name=down.example") {
+            let mut res = Response::new(Body::empty());
+            *res.status_mut() = hyper::StatusCode::INTERNAL_SERVER_ERROR;
+            return Ok(res);
+        } else if query.contains("type=a&") || query.ends_with("type=a") {
+            r#"{"Status":0,"Answer":[{"name":"example.com.","type":1,"TTL":300,"data":"192.0.2.1"}]}"#
+        } else {
+            r#"{"Status":0}"#
+        };
+        Ok(Response::new(Body::from(body)))
+    }
+}
+
+fn dns() -> Dns<NamesClient, ServerConfig> {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    Dns::with_servers(&[server]).unwrap()
+}
+
+#[tokio::test]
+async fn checks_whether_records_exist() {
+    let dns = dns();
+    assert!(dns.has_record("example.com", "A").await.unwrap());
+    assert!(!dns.has_record("example.com", "MX").await.unwrap());
+    assert!(!dns.has_record("nope.example", "A").await.unwrap());
+}
+
+#[tokio::test]
+async fn returns_errors_of_failed_queries() {
+    let dns = dns();
+    assert!(dns.has_record("down.example", "A").await.is_err());
+    assert!(dns.has_record("example.com", "bogus").await.is_err());
+}
+
+#[tokio::test]
+async fn ignores_answer_post_processing() {
+    let dns = dns()
+        .with_keep_cnames(true)
+        .with_max_answers(0)
+        .with_transform(|_, _, _| Vec::new());
+    assert!(!dns.has_record("alias.example", "MX").await.unwrap());
+    assert!(dns.has_record("example.com", "A").await.unwrap());
+}
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{
+    header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE},
+    Body, Response, Result as HyperResult, Uri,
+};
+use std::time::Duration;
+
+// Client that answers with the `Accept-Language` header of the request as a TXT record.
+#[derive(Default)]
+struct HeaderEchoClient;
+
+#[async_trait]
+impl DnsClient for HeaderEchoClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        self.get_with_headers(uri, &HeaderMap::new()).await
+    }
+
+    async fn get_with_headers(
+        &self,
+        _uri: Uri,
+        headers: &HeaderMap,
+    ) -> HyperResult<Response<Body>> {
+        let language = headers
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        Ok(Response::new(Body::from(format!(
+            r#"{{"Status":0,"Answer":[{{"name":"example.com.","type":16,"TTL":300,"data":"{}"}}]}}"#,
+            language
+        ))))
+    }
+}
+
+#[tokio::test]
+async fn sends_headers_with_requests() {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("de-CH"));
+    let dns: Dns<HeaderEchoClient, ServerConfig> =
+        Dns::with_servers(&[server]).unwrap().with_headers(headers);
+    let answers = dns.resolve_txt("example.com").await.unwrap();
+    assert_eq!(answers[0].data, "de-CH");
+}
@@ -0,0 +1,14 @@
+use doh_dns::records::{classify_ipv6, Ipv6Class};
+
+#[test]
+fn classifies_ipv6_addresses() {
+    let class = |addr: &str| classify_ipv6(&addr.parse().unwrap());
+    assert_eq!(class("2606:4700:4700::1111"), Ipv6Class::GlobalUnicast);
+    assert_eq!(class("fd12:3456::1"), Ipv6Class::UniqueLocal);
+    assert_eq!(class("fe80::1"), Ipv6Class::LinkLocal);
+    assert_eq!(class("2001:db8::1"), Ipv6Class::Documentation);
+    assert_eq!(class("ff02::1"), Ipv6Class::Multicast);
+    assert_eq!(class("::1"), Ipv6Class::Loopback);
+    assert_eq!(class("::ffff:192.0.2.1"), Ipv6Class::Other);
+    assert!(!class("fe80::1").is_global());
+}
@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use doh_dns::{
+    client::DnsClient,
+    error::{DnsError, QueryError},
+    server::ServerConfig,
+    Dns,
+};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that answers with a response deviating from the documented schema. `quirky.example`
+// gives numbers as strings and a single record without its array, and `bad.example` gives a
+// TTL that is not a number.
+#[derive(Default)]
+struct QuirkyClient;
+
+#[async_trait]
+impl DnsClient for QuirkyClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let body = match uri.host() {
+            Some("bad.example") => {
+                r#"{"Status":0,"Answer":[{"name":"example.com.","type":1,"TTL":"soon","data":"192.0.2.1"}]}"#
+            }
+            _ => {
+                r#"{"Status":"0","TC":"false","RD":1,"Answer":{"name":"example.com.","type":"1","TTL":"300","data":"192.0.2.1"}}"#
+            }
+        };
+        Ok(Response::new(Body::from(body)))
+    }
+}
+
+fn dns(host: &str, lenient: bool) -> Dns<QuirkyClient, ServerConfig> {
+    let uri = format!("https://{}/resolve", host);
+    let server = ServerConfig::custom(&uri, Duration::from_secs(1));
+    Dns::with_servers(&[server])
+        .unwrap()
+        .with_lenient_parsing(lenient)
+}
+
+fn parse_error(res: Result<Vec<doh_dns::DnsAnswer>, DnsError>) -> String {
+    match res {
+        Err(DnsError::Query(e)) => match e.root() {
+            QueryError::ParseResponse(e) => e.clone(),
+            e => panic!("unexpected error: {}", e),
+        },
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
+
+#[tokio::test]
+async fn coerces_provider_quirks() {
+    let answers = dns("quirky.example", true)
+        .resolve_a("example.com")
+        .await
+        .unwrap();
+    assert_eq!(answers.len(), 1);
+    assert_eq!(answers[0].TTL, 300);
+    assert_eq!(answers[0].data, "192.0.2.1");
+}
+
+#[tokio::test]
+async fn rejects_quirks_in_strict_mode() {
+    parse_error(dns("quirky.example", false).resolve_a("example.com").await);
+}
+
+#[tokio::test]
+async fn names_field_that_cannot_be_coerced() {
+    let error = parse_error(dns("bad.example", true).resolve_a("example.com").await);
+    assert!(error.contains("`Answer[0].TTL`"), "{}", error);
+}
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, error::DnsError, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that fails the test if any request is made.
+#[derive(Default)]
+struct NoNetworkClient;
+
+#[async_trait]
+impl DnsClient for NoNetworkClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        panic!("unexpected request to {}", uri)
+    }
+}
+
+fn dns() -> Dns<NoNetworkClient, ServerConfig> {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    Dns::with_servers(&[server])
+        .unwrap()
+        .with_local_names(&["localhost", ".local"])
+}
+
+#[tokio::test]
+async fn resolves_localhost_to_loopback() {
+    let dns = dns();
+    let answers = dns.resolve_a("LocalHost.").await.unwrap();
+    assert_eq!(answers.len(), 1);
+    assert_eq!(answers[0].data, "127.0.0.1");
+    let answers = dns.resolve_aaaa("app.localhost").await.unwrap();
+    assert_eq!(answers[0].data, "::1");
+}
+
+#[tokio::test]
+async fn rejects_other_local_names() {
+    let dns = dns();
+    assert!(matches!(
+        dns.resolve_a("printer.local").await,
+        Err(DnsError::LocalName(ref name)) if name == "printer.local"
+    ));
+}
+
+#[tokio::test]
+async fn compares_local_names_without_requests() {
+    let dns = dns();
+    let server = ServerConfig::custom("https://other.example/resolve", Duration::from_secs(1));
+    let diff = dns
+        .compare("localhost", "a", &server, &server)
+        .await
+        .unwrap();
+    assert_eq!(diff.common[0].data, "127.0.0.1");
+    assert!(diff.only_a.is_empty() && diff.only_b.is_empty());
+    assert!(matches!(
+        dns.compare("printer.local", "a", &server, &server).await,
+        Err(DnsError::LocalName(_))
+    ));
+}
@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that answers every query with an alias chain of two CNAME records followed by two
+// addresses.
+#[derive(Default)]
+struct ChainClient;
+
+#[async_trait]
+impl DnsClient for ChainClient {
+    async fn get(&self, _uri: Uri) -> HyperResult<Response<Body>> {
+        Ok(Response::new(Body::from(
+            r#"{"Status":0,"Answer":[
+                {"name":"example.com.","type":5,"TTL":300,"data":"a.example.net."},
+                {"name":"a.example.net.","type":5,"TTL":300,"data":"b.example.net."},
+                {"name":"b.example.net.","type":1,"TTL":300,"data":"192.0.2.1"},
+                {"name":"b.example.net.","type":1,"TTL":300,"data":"192.0.2.2"}]}"#,
+        )))
+    }
+}
+
+fn dns() -> Dns<ChainClient, ServerConfig> {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    Dns::with_servers(&[server]).unwrap()
+}
+
+#[tokio::test]
+async fn limits_answers_of_requested_type() {
+    let answers = dns()
+        .with_max_answers(1)
+        .resolve_a("example.com")
+        .await
+        .unwrap();
+    let data = answers.iter().map(|a| a.data.as_str()).collect::<Vec<_>>();
+    assert_eq!(data, vec!["192.0.2.1"]);
+}
+
+#[tokio::test]
+async fn does_not_count_kept_cnames() {
+    let answers = dns()
+        .with_keep_cnames(true)
+        .with_max_answers(1)
+        .resolve_a("example.com")
+        .await
+        .unwrap();
+    let data = answers.iter().map(|a| a.data.as_str()).collect::<Vec<_>>();
+    assert_eq!(data, vec!["a.example.net.", "b.example.net.", "192.0.2.1"]);
+}
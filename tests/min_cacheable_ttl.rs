@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that answers every query with an address whose TTL is the queried name's first
+// label, e.g. `5.example.com` has a TTL of 5 seconds.
+#[derive(Default)]
+struct TtlClient;
+
+#[async_trait]
+impl DnsClient for TtlClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let ttl = uri
+            .query()
+            .and_then(|q| q.strip_prefix("name="))
+            .and_then(|name| name.split('.').next())
+            .unwrap_or("0")
+            .to_string();
+        let body = format!(
+            r#"{{"Status":0,"Answer":[{{"name":"example.com.","type":1,"TTL":{},"data":"192.0.2.1"}}]}}"#,
+            ttl
+        );
+        Ok(Response::new(Body::from(body)))
+    }
+}
+
+#[tokio::test]
+async fn skips_caching_ephemeral_answers() {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    let dns: Dns<TtlClient, ServerConfig> = Dns::with_servers(&[server])
+        .unwrap()
+        .with_cache(true)
+        .with_min_cacheable_ttl(Duration::from_secs(10));
+    for name in [
+        "5.example.com",
+        "5.example.com",
+        "300.example.com",
+        "300.example.com",
+    ] {
+        dns.resolve_a(name).await.unwrap();
+    }
+    let stats = dns.stats();
+    assert_eq!(stats.cache_hits, 1);
+    assert_eq!(stats.cache_misses, 3);
+}
@@ -0,0 +1,31 @@
+use doh_dns::{
+    records::{NsecRecord, Record},
+    DnsAnswer,
+};
+
+fn nsec(data: &str) -> DnsAnswer {
+    DnsAnswer {
+        name: "example.com.".to_string(),
+        r#type: 47,
+        TTL: 3600,
+        data: data.to_string(),
+    }
+}
+
+#[test]
+fn parses_nsec_type_bitmap() {
+    assert_eq!(
+        nsec("www.example.com. A NS SOA rrsig NSEC DNSKEY TYPE65")
+            .parse()
+            .unwrap(),
+        Record::Nsec(NsecRecord {
+            next_domain: "www.example.com.".to_string(),
+            types: vec![1, 2, 6, 46, 47, 48, 65],
+        })
+    );
+}
+
+#[test]
+fn rejects_unknown_type_mnemonics() {
+    assert!(nsec("www.example.com. A BOGUS").parse().is_err());
+}
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that answers every query with an address and an OPT pseudo-record.
+#[derive(Default)]
+struct OptClient;
+
+#[async_trait]
+impl DnsClient for OptClient {
+    async fn get(&self, _uri: Uri) -> HyperResult<Response<Body>> {
+        Ok(Response::new(Body::from(
+            r#"{"Status":0,"Answer":[{"name":"example.com.","type":1,"TTL":300,"data":"192.0.2.1"},{"name":".","type":41,"TTL":0,"data":"1232"}]}"#,
+        )))
+    }
+}
+
+#[tokio::test]
+async fn filters_opt_from_any_answers() {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    let dns: Dns<OptClient, ServerConfig> = Dns::with_servers(&[server]).unwrap();
+    let answers = dns.resolve_any("example.com").await.unwrap();
+    assert_eq!(answers.len(), 1);
+    assert_eq!(dns.rtype_to_name(answers[0].r#type), "A");
+    assert_eq!(dns.rtype_to_name(41), "OPT");
+}
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Client that records the host of every request. `flaky.example` fails its first two
+// requests with a 502 status, `busy.example` and `swamped.example` fail their first request
+// with a 503 status asking to retry after a second and a minute, and other servers answer
+// with an address.
+#[derive(Default)]
+struct FlakyClient {
+    hosts: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl DnsClient for FlakyClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let host = uri.host().unwrap_or_default().to_string();
+        let mut hosts = self.hosts.lock().unwrap();
+        let previous = hosts.iter().filter(|h| **h == host).count();
+        hosts.push(host.clone());
+        let res = match host.as_str() {
+            "flaky.example" if previous < 2 => Response::builder().status(502),
+            "busy.example" if previous < 1 => {
+                Response::builder().status(503).header("Retry-After", "1")
+            }
+            "swamped.example" if previous < 1 => {
+                Response::builder().status(503).header("Retry-After", "60")
+            }
+            _ => Response::builder(),
+        };
+        Ok(res
+            .body(Body::from(
+                r#"{"Status":0,"Answer":[{"name":"example.com.","type":1,"TTL":300,"data":"192.0.2.1"}]}"#,
+            ))
+            .unwrap())
+    }
+}
+
+fn dns(
+    first: &str,
+    timeout: Duration,
+) -> (Dns<FlakyClient, ServerConfig>, Arc<Mutex<Vec<String>>>) {
+    let servers = [
+        ServerConfig::custom(first, timeout),
+        ServerConfig::custom("https://up.example/resolve", Duration::from_secs(1)),
+    ];
+    let client = FlakyClient::default();
+    let hosts = client.hosts.clone();
+    (Dns::with_client(client, &servers).unwrap(), hosts)
+}
+
+#[tokio::test]
+async fn retries_on_same_server() {
+    let (dns, hosts) = dns("https://flaky.example/resolve", Duration::from_secs(1));
+    let dns = dns.with_per_server_retries(2);
+    let start = Instant::now();
+    dns.resolve_a("example.com").await.unwrap();
+    // Backoff of 100 then 200 milliseconds.
+    assert!(start.elapsed() >= Duration::from_millis(300));
+    assert_eq!(*hosts.lock().unwrap(), ["flaky.example"; 3]);
+    assert_eq!(dns.stats().retries, 2);
+}
+
+#[tokio::test]
+async fn tries_next_server_after_retries() {
+    let (dns, hosts) = dns("https://flaky.example/resolve", Duration::from_secs(1));
+    let dns = dns.with_per_server_retries(1);
+    dns.resolve_a("example.com").await.unwrap();
+    assert_eq!(
+        *hosts.lock().unwrap(),
+        ["flaky.example", "flaky.example", "up.example"]
+    );
+}
+
+#[tokio::test]
+async fn waits_retry_after() {
+    let (dns, hosts) = dns("https://busy.example/resolve", Duration::from_secs(2));
+    let dns = dns.with_per_server_retries(1);
+    let start = Instant::now();
+    dns.resolve_a("example.com").await.unwrap();
+    // The second asked by the server is waited instead of the backoff of 100 milliseconds.
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert_eq!(*hosts.lock().unwrap(), ["busy.example"; 2]);
+}
+
+#[tokio::test]
+async fn waits_retry_after_up_to_server_timeout() {
+    // The server uses the default timeout.
+    let (dns, hosts) = dns("https://swamped.example/resolve", Duration::from_secs(0));
+    let dns = dns
+        .with_per_server_retries(1)
+        .with_default_timeout(Duration::from_millis(300));
+    let start = Instant::now();
+    dns.resolve_a("example.com").await.unwrap();
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(300), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(1), "{:?}", elapsed);
+    assert_eq!(*hosts.lock().unwrap(), ["swamped.example"; 2]);
+}
@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, error::DnsError, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Client that records the host of every request and answers with an address.
+#[derive(Default)]
+struct RecordingClient {
+    hosts: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl DnsClient for RecordingClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let host = uri.host().unwrap_or_default().to_string();
+        self.hosts.lock().unwrap().push(host);
+        Ok(Response::new(Body::from(
+            r#"{"Status":0,"Answer":[{"name":"example.com.","type":1,"TTL":300,"data":"192.0.2.1"}]}"#,
+        )))
+    }
+}
+
+fn dns() -> (Dns<RecordingClient, ServerConfig>, Arc<Mutex<Vec<String>>>) {
+    let servers = [
+        ServerConfig::custom("https://first.example/resolve", Duration::from_secs(1)),
+        ServerConfig::custom("https://second.example/resolve", Duration::from_secs(1)),
+    ];
+    let client = RecordingClient::default();
+    let hosts = client.hosts.clone();
+    (Dns::with_client(client, &servers).unwrap(), hosts)
+}
+
+#[tokio::test]
+async fn tries_server_picked_by_policy_first() {
+    let (dns, hosts) = dns();
+    let dns = dns.with_cache(true);
+    let last = |servers: &[ServerConfig]| servers.len() - 1;
+    dns.resolve_with_policy("example.com", "a", last)
+        .await
+        .unwrap();
+    // The second query is answered from the cache.
+    dns.resolve_with_policy("example.com", "a", last)
+        .await
+        .unwrap();
+    assert_eq!(*hosts.lock().unwrap(), vec!["second.example"]);
+}
+
+#[tokio::test]
+async fn does_not_send_local_names() {
+    let (dns, hosts) = dns();
+    let dns = dns.with_local_names(&[".local"]);
+    assert!(matches!(
+        dns.resolve_with_policy("printer.local", "a", |_| 1).await,
+        Err(DnsError::LocalName(_))
+    ));
+    assert!(hosts.lock().unwrap().is_empty());
+}
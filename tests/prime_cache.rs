@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns, DnsAnswer};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that fails the test if any request is made.
+#[derive(Default)]
+struct NoNetworkClient;
+
+#[async_trait]
+impl DnsClient for NoNetworkClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        panic!("unexpected request to {}", uri)
+    }
+}
+
+fn answer(data: &str) -> DnsAnswer {
+    DnsAnswer {
+        name: "example.com.".to_string(),
+        r#type: 1,
+        TTL: 300,
+        data: data.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn resolves_primed_answers_without_requests() {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    let dns: Dns<NoNetworkClient, ServerConfig> =
+        Dns::with_servers(&[server]).unwrap().with_cache(true);
+    dns.prime_cache("Example.com.", "A", vec![answer("192.0.2.1")], None)
+        .unwrap();
+    dns.prime_cache(
+        "example.org",
+        "a",
+        vec![answer("192.0.2.2")],
+        Some(Duration::from_secs(60)),
+    )
+    .unwrap();
+    let answers = dns.resolve_a("example.com").await.unwrap();
+    assert_eq!(answers.len(), 1);
+    assert_eq!(answers[0].data, "192.0.2.1");
+    let answers = dns.resolve_a("example.org").await.unwrap();
+    assert_eq!(answers[0].data, "192.0.2.2");
+    assert_eq!(dns.stats().cache_hits, 2);
+}
@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, error::DnsError, server::ServerConfig, status::RCode, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that answers with the sample response of the provider named by the host of the
+// server, e.g. `google_legacy.example` answers with `fixtures/google_legacy.json`.
+#[derive(Default)]
+struct FixtureClient;
+
+#[async_trait]
+impl DnsClient for FixtureClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let body = match uri.host().and_then(|h| h.strip_suffix(".example")) {
+            Some("google") => include_str!("fixtures/google.json"),
+            Some("google_legacy") => include_str!("fixtures/google_legacy.json"),
+            Some("cloudflare") => include_str!("fixtures/cloudflare.json"),
+            Some("cloudflare_nxdomain") => include_str!("fixtures/cloudflare_nxdomain.json"),
+            host => panic!("no fixture for {:?}", host),
+        };
+        Ok(Response::new(Body::from(body)))
+    }
+}
+
+fn dns(provider: &str) -> Dns<FixtureClient, ServerConfig> {
+    let uri = format!("https://{}.example/resolve", provider);
+    Dns::with_servers(&[ServerConfig::custom(&uri, Duration::from_secs(1))]).unwrap()
+}
+
+#[tokio::test]
+async fn parses_responses_of_each_provider() {
+    for provider in ["google", "google_legacy", "cloudflare"] {
+        let reply = dns(provider)
+            .resolve_with_flags("example.com", "A")
+            .await
+            .unwrap();
+        assert_eq!(reply.answers.len(), 1, "{}", provider);
+        assert_eq!(reply.answers[0].data, "93.184.216.34", "{}", provider);
+    }
+}
+
+#[tokio::test]
+async fn joins_comment_lists() {
+    let reply = dns("google_legacy")
+        .resolve_with_flags("example.com", "A")
+        .await
+        .unwrap();
+    assert_eq!(
+        reply.comment.as_deref(),
+        Some("Response from 199.43.135.53. Cached")
+    );
+}
+
+#[tokio::test]
+async fn parses_negative_responses() {
+    assert!(matches!(
+        dns("cloudflare_nxdomain")
+            .resolve_a("nope.example.com")
+            .await,
+        Err(DnsError::Status(RCode::NXDomain))
+    ));
+}
@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use doh_dns::{
+    client::DnsClient,
+    error::{DnsError, QueryError},
+    server::ServerConfig,
+    Dns,
+};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that answers every query with a PTR record whose data is the queried name.
+#[derive(Default)]
+struct EchoClient;
+
+#[async_trait]
+impl DnsClient for EchoClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let name = uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .find_map(|param| param.strip_prefix("name="))
+            .unwrap_or_default()
+            .to_string();
+        let body = format!(
+            r#"{{"Status":0,"Answer":[{{"name":"{0}.","type":12,"TTL":300,"data":"{0}."}}]}}"#,
+            name
+        );
+        Ok(Response::new(Body::from(body)))
+    }
+}
+
+fn dns() -> Dns<EchoClient, ServerConfig> {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    Dns::with_servers(&[server]).unwrap()
+}
+
+#[tokio::test]
+async fn resolves_ptr_for_ip_strings() {
+    let dns = dns();
+    let answers = dns.resolve_ptr_for_ip_str("93.184.216.34").await.unwrap();
+    assert_eq!(answers[0].data, "34.216.184.93.in-addr.arpa.");
+    let answers = dns.resolve_ptr_for_ip_str("2001:db8::1").await.unwrap();
+    assert_eq!(
+        answers[0].data,
+        "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."
+    );
+}
+
+#[tokio::test]
+async fn rejects_invalid_ip_strings() {
+    assert!(matches!(
+        dns().resolve_ptr_for_ip_str("93.184.216").await,
+        Err(DnsError::Query(QueryError::InvalidName(_)))
+    ));
+}
+
+#[tokio::test]
+async fn returns_issued_query() {
+    let issued = dns().resolve_issued("Bücher.de.", "ptr").await.unwrap();
+    assert_eq!(issued.issued_name, "xn--bcher-kva.de");
+    assert_eq!(issued.issued_type, 12);
+    assert_eq!(issued.answers[0].data, "xn--bcher-kva.de.");
+}
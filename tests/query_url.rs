@@ -0,0 +1,119 @@
+#![cfg(feature = "hyper-client")]
+use doh_dns::{
+    client::HyperDnsClient,
+    error::{DnsError, QueryError},
+    server::{ServerConfig, DEFAULT_CLOUDFLARE_TIMEOUT, DEFAULT_GOOGLE_TIMEOUT, DEFAULT_SERVERS},
+    Dns, DnsFlags, DnsHttpsServer, IdnaConfig,
+};
+use std::time::Duration;
+
+fn server(uri: &str) -> ServerConfig {
+    ServerConfig::custom(uri, Duration::from_secs(1))
+}
+
+fn dns(server: &ServerConfig) -> Dns<HyperDnsClient, ServerConfig> {
+    Dns::with_servers(std::slice::from_ref(server)).unwrap()
+}
+
+#[test]
+fn builds_query_url() {
+    let server = server("https://dns.example/resolve");
+    let dns = dns(&server);
+    assert_eq!(
+        dns.build_query_url(&server, "Example.com.", "mx").unwrap(),
+        "https://dns.example/resolve?name=example.com&type=mx"
+    );
+    assert_eq!(
+        dns.build_query_url(&server, "bücher.de", "a").unwrap(),
+        "https://dns.example/resolve?name=xn--bcher-kva.de&type=a"
+    );
+}
+
+#[test]
+fn appends_to_existing_query() {
+    let server = server("https://dns.example/resolve?ct=application/dns-json");
+    let dns = dns(&server);
+    assert_eq!(
+        dns.build_query_url(&server, "example.com", "aaaa").unwrap(),
+        "https://dns.example/resolve?ct=application/dns-json&name=example.com&type=aaaa"
+    );
+}
+
+#[test]
+fn rejects_unknown_record_type() {
+    let server = server("https://dns.example/resolve");
+    let dns = dns(&server);
+    assert!(matches!(
+        dns.build_query_url(&server, "example.com", "nope"),
+        Err(DnsError::InvalidRecordType)
+    ));
+}
+
+#[test]
+fn appends_server_query_params() {
+    let server =
+        server("https://dns.example/resolve").with_query_param("ct", "application/x-javascript");
+    let dns = dns(&server);
+    assert_eq!(
+        dns.build_query_url(&server, "example.com", "a").unwrap(),
+        "https://dns.example/resolve?name=example.com&type=a&ct=application/x-javascript"
+    );
+}
+
+#[test]
+fn applies_idna_config() {
+    let server = server("https://dns.example/resolve");
+    let dns = dns(&server);
+    assert_eq!(
+        dns.build_query_url(&server, "faß.de", "a").unwrap(),
+        "https://dns.example/resolve?name=xn--fa-hia.de&type=a"
+    );
+    let dns = dns.with_idna_config(IdnaConfig {
+        transitional: true,
+        ..IdnaConfig::default()
+    });
+    assert_eq!(
+        dns.build_query_url(&server, "faß.de", "a").unwrap(),
+        "https://dns.example/resolve?name=fass.de&type=a"
+    );
+}
+
+#[test]
+fn appends_header_flags() {
+    let server = server("https://dns.example/resolve");
+    let dns = dns(&server).with_flags(DnsFlags {
+        recursion_desired: false,
+        checking_disabled: true,
+        dnssec_ok: true,
+        ..DnsFlags::default()
+    });
+    assert_eq!(
+        dns.build_query_url(&server, "example.com", "a").unwrap(),
+        "https://dns.example/resolve?name=example.com&type=a&rd=0&cd=1&do=1"
+    );
+}
+
+#[test]
+fn strips_one_trailing_dot() {
+    let server = server("https://dns.example/resolve");
+    let dns = dns(&server);
+    assert_eq!(
+        dns.build_query_url(&server, "example.com.", "a").unwrap(),
+        dns.build_query_url(&server, "example.com", "a").unwrap()
+    );
+    assert!(matches!(
+        dns.build_query_url(&server, "example.com..", "a"),
+        Err(DnsError::Query(QueryError::InvalidName(_)))
+    ));
+}
+
+#[test]
+fn default_uses_default_servers() {
+    let dns: Dns<HyperDnsClient, ServerConfig> = Dns::default();
+    let google = dns
+        .build_query_url(&DEFAULT_SERVERS[0], "example.com", "A")
+        .unwrap();
+    assert!(google.starts_with("https://dns.google/resolve?"));
+    assert_eq!(DEFAULT_SERVERS[0].timeout(), DEFAULT_GOOGLE_TIMEOUT);
+    assert_eq!(DEFAULT_SERVERS[1].timeout(), DEFAULT_CLOUDFLARE_TIMEOUT);
+}
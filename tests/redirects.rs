@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client for which `old.example` redirects to `new.example`, `loop.example` redirects to
+// itself and `http.example` redirects to a plain `http` URL.
+#[derive(Default)]
+struct MovedClient;
+
+#[async_trait]
+impl DnsClient for MovedClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let location = match uri.host() {
+            Some("old.example") => "https://new.example/resolve?name=example.com&type=A",
+            Some("loop.example") => "https://loop.example/resolve",
+            Some("http.example") => "http://new.example/resolve?name=example.com&type=A",
+            _ => {
+                return Ok(Response::new(Body::from(
+                    r#"{"Status":0,"Answer":[{"name":"example.com.","type":1,"TTL":300,"data":"192.0.2.1"}]}"#,
+                )))
+            }
+        };
+        Ok(Response::builder()
+            .status(301)
+            .header("Location", location)
+            .body(Body::empty())
+            .unwrap())
+    }
+}
+
+fn dns(uri: &str, max: usize) -> Dns<MovedClient, ServerConfig> {
+    let server = ServerConfig::custom(uri, Duration::from_secs(1));
+    Dns::with_servers(&[server]).unwrap().with_redirects(max)
+}
+
+#[tokio::test]
+async fn follows_https_redirects() {
+    let answers = dns("https://old.example/resolve", 2)
+        .resolve_a("example.com")
+        .await
+        .unwrap();
+    assert_eq!(answers[0].data, "192.0.2.1");
+}
+
+#[tokio::test]
+async fn rejects_redirects_when_disabled_or_unsafe() {
+    assert!(dns("https://old.example/resolve", 0)
+        .resolve_a("example.com")
+        .await
+        .is_err());
+    assert!(dns("https://http.example/resolve", 2)
+        .resolve_a("example.com")
+        .await
+        .is_err());
+    assert!(dns("https://loop.example/resolve", 2)
+        .resolve_a("example.com")
+        .await
+        .is_err());
+}
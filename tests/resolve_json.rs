@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+const BODY: &str = r#"{"Status":3,"Question":[{"name":"nope.example.","type":1}],"edns_client_subnet":"192.0.2.0/24"}"#;
+
+// Client that answers every query with the same body.
+#[derive(Default)]
+struct FixedClient;
+
+#[async_trait]
+impl DnsClient for FixedClient {
+    async fn get(&self, _uri: Uri) -> HyperResult<Response<Body>> {
+        Ok(Response::new(Body::from(BODY)))
+    }
+}
+
+#[tokio::test]
+async fn returns_response_as_received() {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    let dns: Dns<FixedClient, ServerConfig> = Dns::with_servers(&[server]).unwrap();
+    let value = dns.resolve_json("nope.example", "A").await.unwrap();
+    assert_eq!(
+        value,
+        serde_json::from_str::<serde_json::Value>(BODY).unwrap()
+    );
+}
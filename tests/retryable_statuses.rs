@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use doh_dns::{
+    client::DnsClient,
+    error::{DnsError, QueryError},
+    server::ServerConfig,
+    Dns,
+};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Client that records the host of every request. `bad.example` fails with a 400 status,
+// `error.example` with a 500 status, and other servers answer with an address.
+#[derive(Default)]
+struct StatusClient {
+    hosts: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl DnsClient for StatusClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let host = uri.host().unwrap_or_default().to_string();
+        let status = match host.as_str() {
+            "bad.example" => 400,
+            "error.example" => 500,
+            _ => 200,
+        };
+        self.hosts.lock().unwrap().push(host);
+        Ok(Response::builder()
+            .status(status)
+            .body(Body::from(
+                r#"{"Status":0,"Answer":[{"name":"example.com.","type":1,"TTL":300,"data":"192.0.2.1"}]}"#,
+            ))
+            .unwrap())
+    }
+}
+
+fn dns(first: &str) -> (Dns<StatusClient, ServerConfig>, Arc<Mutex<Vec<String>>>) {
+    let servers = [
+        ServerConfig::custom(first, Duration::from_secs(1)),
+        ServerConfig::custom("https://up.example/resolve", Duration::from_secs(1)),
+    ];
+    let client = StatusClient::default();
+    let hosts = client.hosts.clone();
+    (Dns::with_client(client, &servers).unwrap(), hosts)
+}
+
+#[tokio::test]
+async fn aborts_on_request_errors_by_default() {
+    let (dns, hosts) = dns("https://bad.example/resolve");
+    let res = dns.resolve_a("example.com").await;
+    assert!(matches!(
+        res.map_err(|e| match e {
+            DnsError::Query(e) => e.root().clone(),
+            e => panic!("unexpected error: {}", e),
+        }),
+        Err(QueryError::BadRequest400)
+    ));
+    assert_eq!(*hosts.lock().unwrap(), vec!["bad.example"]);
+}
+
+#[tokio::test]
+async fn retries_default_statuses_on_next_server() {
+    let (dns, hosts) = dns("https://error.example/resolve");
+    dns.resolve_a("example.com").await.unwrap();
+    assert_eq!(*hosts.lock().unwrap(), vec!["error.example", "up.example"]);
+}
+
+#[tokio::test]
+async fn aborts_on_statuses_removed_from_set() {
+    let (dns, hosts) = dns("https://error.example/resolve");
+    let dns = dns.with_retryable_statuses(&[429, 502, 503, 504]);
+    let res = dns.resolve_a("example.com").await;
+    assert!(matches!(
+        res.map_err(|e| match e {
+            DnsError::Query(e) => e.root().clone(),
+            e => panic!("unexpected error: {}", e),
+        }),
+        Err(QueryError::InternalServerError500)
+    ));
+    assert_eq!(*hosts.lock().unwrap(), vec!["error.example"]);
+}
+
+#[tokio::test]
+async fn retries_configured_statuses_on_next_server() {
+    let (dns, hosts) = dns("https://bad.example/resolve");
+    let dns = dns.with_retryable_statuses(&[400]);
+    dns.resolve_a("example.com").await.unwrap();
+    assert_eq!(*hosts.lock().unwrap(), vec!["bad.example", "up.example"]);
+}
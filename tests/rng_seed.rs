@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, strategy::Strategy, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Client that records the host of every request and answers without records.
+#[derive(Default)]
+struct RecordingClient {
+    hosts: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl DnsClient for RecordingClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let host = uri.host().unwrap_or_default().to_string();
+        self.hosts.lock().unwrap().push(host);
+        Ok(Response::new(Body::from(r#"{"Status":0}"#)))
+    }
+}
+
+async fn hosts_with_seed(seed: u64) -> Vec<String> {
+    let servers = [
+        ServerConfig::custom("https://a.example/resolve", Duration::from_secs(1)),
+        ServerConfig::custom("https://b.example/resolve", Duration::from_secs(1)),
+    ];
+    let client = RecordingClient::default();
+    let hosts = client.hosts.clone();
+    let dns = Dns::with_client(client, &servers)
+        .unwrap()
+        .with_strategy(Strategy::Weighted(vec![50, 50]))
+        .with_rng_seed(seed);
+    for _ in 0..20 {
+        dns.resolve_a("example.com").await.unwrap();
+    }
+    let hosts = hosts.lock().unwrap().clone();
+    hosts
+}
+
+#[tokio::test]
+async fn seeded_instances_choose_the_same_servers() {
+    let hosts = hosts_with_seed(7).await;
+    assert_eq!(hosts, hosts_with_seed(7).await);
+    assert!(hosts.iter().any(|h| h == "a.example"));
+    assert!(hosts.iter().any(|h| h == "b.example"));
+}
@@ -0,0 +1,43 @@
+use doh_dns::{
+    records::{Record, RrsigRecord},
+    DnsAnswer,
+};
+use std::time::{Duration, UNIX_EPOCH};
+
+fn rrsig(data: &str) -> DnsAnswer {
+    DnsAnswer {
+        name: "example.com.".to_string(),
+        r#type: 46,
+        TTL: 3600,
+        data: data.to_string(),
+    }
+}
+
+#[test]
+fn parses_rrsig_fields() {
+    assert_eq!(
+        rrsig("a 13 2 300 20240229120000 1700000000 12345 example.com. abc def=")
+            .parse()
+            .unwrap(),
+        Record::Rrsig(RrsigRecord {
+            type_covered: 1,
+            algorithm: 13,
+            labels: 2,
+            original_ttl: 300,
+            expiration: UNIX_EPOCH + Duration::from_secs(1_709_208_000),
+            inception: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            key_tag: 12345,
+            signer_name: "example.com.".to_string(),
+            signature: "abcdef=".to_string(),
+        })
+    );
+}
+
+#[test]
+fn rejects_invalid_timestamps() {
+    assert!(
+        rrsig("A 13 2 300 20241301000000 20240101000000 1 example.com. abc")
+            .parse()
+            .is_err()
+    );
+}
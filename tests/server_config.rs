@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use doh_dns::{
+    client::DnsClient,
+    error::{DnsError, QueryError},
+    server::ServerConfig,
+    Dns,
+};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+
+// Client that fails the test if any request is made.
+#[derive(Default)]
+struct NoNetworkClient;
+
+#[async_trait]
+impl DnsClient for NoNetworkClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        panic!("unexpected request to {}", uri)
+    }
+}
+
+#[test]
+fn rejects_custom_server_without_uri() {
+    let servers: Vec<ServerConfig> = serde_json::from_str(
+        r#"[{ "kind": "google", "timeout_secs": 2 }, { "kind": "custom", "timeout_secs": 10 }]"#,
+    )
+    .unwrap();
+    match Dns::<NoNetworkClient, ServerConfig>::with_servers(&servers) {
+        Err(DnsError::Query(QueryError::InvalidEndpoint(e))) => assert!(e.contains("server 1")),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("custom server without URI accepted"),
+    }
+}
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Client that records the path and query of every request and answers after 50
+// milliseconds, so that concurrent queries overlap.
+#[derive(Default)]
+struct SlowClient {
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl DnsClient for SlowClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        self.requests.lock().unwrap().push(uri.to_string());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(Response::new(Body::from(
+            r#"{"Status":0,"Answer":[{"name":"example.com.","type":1,"TTL":300,"data":"192.0.2.1"}]}"#,
+        )))
+    }
+}
+
+fn dns(single_flight: bool) -> (Dns<SlowClient, ServerConfig>, Arc<Mutex<Vec<String>>>) {
+    let servers = [ServerConfig::custom(
+        "https://up.example/resolve",
+        Duration::from_secs(1),
+    )];
+    let client = SlowClient::default();
+    let requests = client.requests.clone();
+    let dns = Dns::with_client(client, &servers)
+        .unwrap()
+        .with_single_flight(single_flight);
+    (dns, requests)
+}
+
+#[tokio::test]
+async fn coalesces_concurrent_identical_queries() {
+    let (dns, requests) = dns(true);
+    let (a, b, c) = tokio::join!(
+        dns.resolve_a("example.com"),
+        dns.resolve_a("example.com"),
+        dns.resolve_a("example.com"),
+    );
+    assert_eq!(a.unwrap(), b.unwrap());
+    assert_eq!(c.unwrap()[0].data, "192.0.2.1");
+    assert_eq!(requests.lock().unwrap().len(), 1);
+    let stats = dns.stats();
+    assert_eq!(stats.coalesced, 2);
+    assert_eq!(stats.requests, 1);
+}
+
+#[tokio::test]
+async fn does_not_coalesce_different_queries() {
+    let (dns, requests) = dns(true);
+    let (a, aaaa) = tokio::join!(
+        dns.resolve_a("example.com"),
+        dns.resolve_aaaa("example.com")
+    );
+    a.unwrap();
+    aaaa.unwrap();
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert_eq!(dns.stats().coalesced, 0);
+}
+
+#[tokio::test]
+async fn sends_every_query_when_disabled() {
+    let (dns, requests) = dns(false);
+    let (a, b) = tokio::join!(dns.resolve_a("example.com"), dns.resolve_a("example.com"));
+    a.unwrap();
+    b.unwrap();
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert_eq!(dns.stats().coalesced, 0);
+}
@@ -0,0 +1,60 @@
+use doh_dns::records::{parse_spf, SpfMechanism, SpfMechanismKind, SpfQualifier};
+
+fn mechanism(qualifier: SpfQualifier, kind: SpfMechanismKind, value: Option<&str>) -> SpfMechanism {
+    SpfMechanism {
+        qualifier,
+        kind,
+        value: value.map(str::to_string),
+    }
+}
+
+#[test]
+fn parses_mechanisms() {
+    let mechanisms = parse_spf(
+        "v=spf1 a mx/24 ip4:192.0.2.0/24 ip6:2001:db8::/32 include:_spf.example.com ~all",
+    )
+    .unwrap();
+    assert_eq!(
+        mechanisms,
+        vec![
+            mechanism(SpfQualifier::Pass, SpfMechanismKind::A, None),
+            mechanism(SpfQualifier::Pass, SpfMechanismKind::Mx, Some("/24")),
+            mechanism(
+                SpfQualifier::Pass,
+                SpfMechanismKind::Ip4,
+                Some("192.0.2.0/24")
+            ),
+            mechanism(
+                SpfQualifier::Pass,
+                SpfMechanismKind::Ip6,
+                Some("2001:db8::/32")
+            ),
+            mechanism(
+                SpfQualifier::Pass,
+                SpfMechanismKind::Include,
+                Some("_spf.example.com")
+            ),
+            mechanism(SpfQualifier::SoftFail, SpfMechanismKind::All, None),
+        ]
+    );
+}
+
+#[test]
+fn skips_modifiers() {
+    let mechanisms = parse_spf("v=spf1 -ip4:192.0.2.1 redirect=_spf.example.com").unwrap();
+    assert_eq!(
+        mechanisms,
+        vec![mechanism(
+            SpfQualifier::Fail,
+            SpfMechanismKind::Ip4,
+            Some("192.0.2.1")
+        )]
+    );
+}
+
+#[test]
+fn rejects_invalid_records() {
+    assert!(parse_spf("v=DMARC1; p=none").is_err());
+    assert!(parse_spf("v=spf1 include -all").is_err());
+    assert!(parse_spf("v=spf1 bogus:example.com").is_err());
+}
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Client that records the host of every request. `down.example` fails with a 500 status and
+// other servers answer without records.
+#[derive(Default)]
+struct RecordingClient {
+    hosts: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl DnsClient for RecordingClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let host = uri.host().unwrap_or_default().to_string();
+        let status = if host == "down.example" { 500 } else { 200 };
+        self.hosts.lock().unwrap().push(host);
+        Ok(Response::builder()
+            .status(status)
+            .body(Body::from(r#"{"Status":0}"#))
+            .unwrap())
+    }
+}
+
+#[tokio::test]
+async fn tries_last_answering_server_first() {
+    let servers = [
+        ServerConfig::custom("https://down.example/resolve", Duration::from_secs(1)),
+        ServerConfig::custom("https://up.example/resolve", Duration::from_secs(1)),
+    ];
+    let client = RecordingClient::default();
+    let hosts = client.hosts.clone();
+    let dns = Dns::with_client(client, &servers)
+        .unwrap()
+        .with_sticky_server(true);
+    dns.resolve_a("example.com").await.unwrap();
+    dns.resolve_a("example.org").await.unwrap();
+    assert_eq!(
+        *hosts.lock().unwrap(),
+        vec!["down.example", "up.example", "up.example"]
+    );
+}
@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client that answers with an alias chain along with an address of an unrelated name.
+#[derive(Default)]
+struct MismatchClient;
+
+#[async_trait]
+impl DnsClient for MismatchClient {
+    async fn get(&self, _uri: Uri) -> HyperResult<Response<Body>> {
+        Ok(Response::new(Body::from(
+            r#"{"Status":0,"Answer":[
+                {"name":"cdn.example.net.","type":1,"TTL":300,"data":"192.0.2.1"},
+                {"name":"www.example.com.","type":5,"TTL":300,"data":"cdn.example.net."},
+                {"name":"evil.example.","type":1,"TTL":300,"data":"198.51.100.1"}
+            ]}"#,
+        )))
+    }
+}
+
+fn dns() -> Dns<MismatchClient, ServerConfig> {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    Dns::with_servers(&[server]).unwrap()
+}
+
+#[tokio::test]
+async fn drops_answers_of_unrelated_names() {
+    assert_eq!(dns().resolve_a("www.example.com").await.unwrap().len(), 2);
+    let answers = dns()
+        .with_strict_name_match(true)
+        .resolve_a("WWW.example.com.")
+        .await
+        .unwrap();
+    assert_eq!(answers.len(), 1);
+    assert_eq!(answers[0].data, "192.0.2.1");
+}
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use doh_dns::{client::DnsClient, server::ServerConfig, Dns};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+// Client that answers `A` and `AAAA` queries with a valid and an invalid address.
+#[derive(Default)]
+struct AddressClient;
+
+#[async_trait]
+impl DnsClient for AddressClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        let (num, data) = match uri.query().unwrap_or_default() {
+            q if q.contains("type=aaaa") => (28, "2001:db8::1"),
+            _ => (1, "192.0.2.1"),
+        };
+        Ok(Response::new(Body::from(format!(
+            r#"{{"Status":0,"Answer":[{{"name":"example.com.","type":{0},"TTL":300,"data":"{1}"}},{{"name":"example.com.","type":{0},"TTL":300,"data":"invalid"}}]}}"#,
+            num, data
+        ))))
+    }
+}
+
+fn dns() -> Dns<AddressClient, ServerConfig> {
+    let server = ServerConfig::custom("https://dns.example/resolve", Duration::from_secs(1));
+    Dns::with_servers(&[server]).unwrap()
+}
+
+#[tokio::test]
+async fn resolves_ipv4_addresses() {
+    assert_eq!(
+        dns().resolve_ipv4("example.com").await.unwrap(),
+        vec![Ipv4Addr::new(192, 0, 2, 1)]
+    );
+}
+
+#[tokio::test]
+async fn resolves_ipv6_addresses() {
+    assert_eq!(
+        dns().resolve_ipv6("example.com").await.unwrap(),
+        vec!["2001:db8::1".parse::<Ipv6Addr>().unwrap()]
+    );
+}
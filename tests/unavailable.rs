@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use doh_dns::{
+    client::DnsClient,
+    error::{DnsError, QueryError},
+    server::ServerConfig,
+    Dns,
+};
+use hyper::{Body, Response, Result as HyperResult, Uri};
+use std::time::Duration;
+
+// Client for which `down.example` is unavailable and other servers answer with an address.
+#[derive(Default)]
+struct MaintenanceClient;
+
+#[async_trait]
+impl DnsClient for MaintenanceClient {
+    async fn get(&self, uri: Uri) -> HyperResult<Response<Body>> {
+        if uri.host() == Some("down.example") {
+            let res = Response::builder()
+                .status(503)
+                .header("Retry-After", "2")
+                .body(Body::empty())
+                .unwrap();
+            return Ok(res);
+        }
+        Ok(Response::new(Body::from(
+            r#"{"Status":0,"Answer":[{"name":"example.com.","type":1,"TTL":300,"data":"192.0.2.1"}]}"#,
+        )))
+    }
+}
+
+fn server(uri: &str) -> ServerConfig {
+    ServerConfig::custom(uri, Duration::from_secs(1))
+}
+
+#[tokio::test]
+async fn retries_unavailable_server_on_next_one() {
+    let servers = [
+        server("https://down.example/resolve"),
+        server("https://up.example/resolve"),
+    ];
+    let dns: Dns<MaintenanceClient, ServerConfig> = Dns::with_servers(&servers).unwrap();
+    assert_eq!(dns.resolve_a("example.com").await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn returns_retry_after_of_unavailable_server() {
+    let dns: Dns<MaintenanceClient, ServerConfig> =
+        Dns::with_servers(&[server("https://down.example/resolve")]).unwrap();
+    match dns.resolve_a("example.com").await {
+        Err(DnsError::Query(e)) => {
+            assert!(matches!(e.root(), QueryError::ServiceUnavailable503(_)));
+            assert_eq!(e.retry_after(), Some(Duration::from_secs(2)));
+        }
+        res => panic!("unexpected result: {:?}", res),
+    }
+}